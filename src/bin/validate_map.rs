@@ -0,0 +1,258 @@
+//! Checks Tiled maps before they ship, using the same parsing as the game's `TiledLoader`.
+//!
+//! Usage: `cargo run --bin validate_map -- [assets/maps/map.tmx ...]`
+//! Without arguments every Tiled map listed in `assets/maps/maps.catalogue.ron` is checked.
+//! Exits with a non-zero status if any map has errors.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use bevy_jam_game::{
+    parse_external_tileset, parse_tiled_map, player_settings, CollisionMap, MapCatalogue,
+    MapObjectKind, MapObjects, MapSettings, MapSource, SpawnPoints, Vec2, MAP_TILE_SIZE,
+};
+
+const ASSETS_DIR: &str = "assets";
+const CATALOGUE_PATH: &str = "assets/maps/maps.catalogue.ron";
+const MIN_PLAYER_SPAWNS: usize = 2;
+
+#[derive(Default)]
+struct Report {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl Report {
+    fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into());
+    }
+
+    fn warning(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+}
+
+fn main() {
+    let mut paths: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        paths = match catalogue_maps() {
+            Ok(paths) => paths,
+            Err(e) => {
+                eprintln!("{}: {}", CATALOGUE_PATH, e);
+                process::exit(2);
+            }
+        };
+    }
+
+    let mut failed = 0;
+    for path in paths.iter() {
+        let report = validate(path);
+
+        println!("{}", path.display());
+        for error in report.errors.iter() {
+            println!("  error: {}", error);
+        }
+        for warning in report.warnings.iter() {
+            println!("  warning: {}", warning);
+        }
+        if report.errors.is_empty() && report.warnings.is_empty() {
+            println!("  ok");
+        }
+
+        if !report.errors.is_empty() {
+            failed += 1;
+        }
+    }
+
+    println!("\n{} map(s) checked, {} with errors", paths.len(), failed);
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+/// Tiled maps listed in the map manifest, generated arenas don't need checking
+fn catalogue_maps() -> anyhow::Result<Vec<PathBuf>> {
+    let catalogue: MapCatalogue = ron::de::from_bytes(&fs::read(CATALOGUE_PATH)?)?;
+
+    Ok(catalogue
+        .maps
+        .into_iter()
+        .filter_map(|entry| match entry.source {
+            MapSource::Tiled(path) => Some(Path::new(ASSETS_DIR).join(path)),
+            MapSource::Generated(_) => None,
+        })
+        .collect())
+}
+
+fn validate(path: &Path) -> Report {
+    let mut report = Report::default();
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            report.error(format!("cannot read file: {}", e));
+            return report;
+        }
+    };
+
+    let (mut map, external_tilesets) = match parse_tiled_map(&bytes) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            report.error(format!("cannot parse map: {}", e));
+            return report;
+        }
+    };
+
+    // Same lookup rules as the loader: image paths are relative to the file declaring them
+    let root_dir = path.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+    let mut image_dirs = vec![(0, root_dir.clone())];
+    for (first_gid, tsx) in external_tilesets {
+        let tsx_path = root_dir.join(&tsx);
+        let tileset = fs::read(&tsx_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| parse_external_tileset(&bytes, first_gid));
+
+        match tileset {
+            Ok(tileset) => {
+                image_dirs.push((first_gid, tsx_path.parent().unwrap_or(&root_dir).to_path_buf()));
+                map.tilesets.push(tileset);
+            }
+            Err(e) => report.error(format!("cannot load tileset {}: {}", tsx, e)),
+        }
+    }
+    map.tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+    if map.tile_width != MAP_TILE_SIZE || map.tile_height != MAP_TILE_SIZE {
+        report.error(format!(
+            "map tiles are {}x{}, the game expects {}x{}",
+            map.tile_width, map.tile_height, MAP_TILE_SIZE, MAP_TILE_SIZE
+        ));
+    }
+
+    for tileset in map.tilesets.iter() {
+        let image_dir = image_dirs
+            .iter()
+            .find(|(gid, _)| *gid == tileset.first_gid)
+            .map_or(&root_dir, |(_, dir)| dir);
+
+        match tileset.images.as_slice() {
+            [image] => {
+                check_image(&mut report, &tileset.name, &image_dir.join(&image.source));
+                if tileset.tile_width != map.tile_width || tileset.tile_height != map.tile_height {
+                    report.warning(format!(
+                        "tileset '{}' has {}x{} tiles, the map grid is {}x{}",
+                        tileset.name,
+                        tileset.tile_width,
+                        tileset.tile_height,
+                        map.tile_width,
+                        map.tile_height
+                    ));
+                }
+            }
+            [] => {
+                for tile in tileset.tiles.iter() {
+                    for image in tile.images.iter() {
+                        check_image(&mut report, &tileset.name, &image_dir.join(&image.source));
+                    }
+                }
+            }
+            _ => report.error(format!("tileset '{}' has more than one image", tileset.name)),
+        }
+    }
+
+    let settings = MapSettings::from_tiled(&map);
+    let collision_map = CollisionMap::from_tiled(&map, &settings);
+    check_spawns(&mut report, &SpawnPoints::from_tiled(&map, &settings), &settings, &collision_map);
+    check_objects(&mut report, &MapObjects::from_tiled(&map, &settings), &settings);
+
+    report
+}
+
+fn check_image(report: &mut Report, tileset: &str, path: &Path) {
+    if !path.is_file() {
+        report.error(format!("tileset '{}' image {} does not exist", tileset, path.display()));
+    }
+}
+
+fn in_bounds(point: Vec2, settings: &MapSettings) -> bool {
+    point.cmpge(settings.min()).all() && point.cmple(settings.max()).all()
+}
+
+fn check_spawns(
+    report: &mut Report,
+    spawns: &SpawnPoints,
+    settings: &MapSettings,
+    collision_map: &CollisionMap,
+) {
+    if spawns.players.len() < MIN_PLAYER_SPAWNS {
+        report.error(format!(
+            "found {} player spawn(s), at least {} are needed",
+            spawns.players.len(),
+            MIN_PLAYER_SPAWNS
+        ));
+    }
+
+    let radius = player_settings::DEFAULT_PLAYER_SIZE / 2.0;
+    for (name, point) in spawns.players.iter() {
+        if !in_bounds(*point, settings) {
+            report.error(format!("player spawn '{}' at {} is outside the map", name, point));
+        } else if collision_map.collides(*point, radius) {
+            report.error(format!("player spawn '{}' at {} is inside a wall", name, point));
+        }
+    }
+
+    if spawns.zombie_zones.is_empty() {
+        report.warning("no zombie zones, zombies will spawn anywhere on the map");
+    }
+    for zone in spawns.zombie_zones.iter() {
+        if !in_bounds(zone.rect.min, settings) || !in_bounds(zone.rect.max, settings) {
+            report
+                .warning(format!("zombie zone at {} reaches outside the map", zone.rect.center()));
+        }
+    }
+}
+
+fn check_objects(report: &mut Report, objects: &MapObjects, settings: &MapSettings) {
+    for object in objects.0.iter() {
+        if !in_bounds(object.rect.center(), settings) {
+            report.error(format!("object '{}' is outside the map", object.name));
+        }
+
+        match &object.kind {
+            MapObjectKind::Teleporter { pair, .. } => {
+                let others = objects
+                    .0
+                    .iter()
+                    .filter(|o| {
+                        matches!(
+                            &o.kind,
+                            MapObjectKind::Teleporter { pair: p, .. } if p == pair
+                        )
+                    })
+                    .count();
+                if others != 2 {
+                    report.warning(format!(
+                        "teleporter '{}' pair '{}' has {} teleporter(s), expected 2",
+                        object.name, pair, others
+                    ));
+                }
+            }
+            MapObjectKind::PressurePlate { door, .. } => {
+                let has_door = objects
+                    .0
+                    .iter()
+                    .any(|o| matches!(&o.kind, MapObjectKind::Door { id, .. } if id == door));
+                if !has_door {
+                    report.warning(format!(
+                        "pressure plate '{}' opens door '{}', which doesn't exist",
+                        object.name, door
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::{
     core_pipeline::clear_color::ClearColorConfig,
-    render::camera::Viewport,
+    math::Vec3Swizzles,
+    render::{camera::Viewport, view::RenderLayers},
     window::{WindowId, WindowResized},
 };
 
@@ -15,10 +16,34 @@ pub struct LeftCamera;
 #[derive(Component)]
 pub struct RightCamera;
 
+/// Overview of the whole arena, spawned next to each viewport's camera, see `MiniMapPlugin`
 #[derive(Component)]
 pub struct MiniMapCamera;
 
 const SCALE: f32 = 0.5;
+/// Widest zoom, reached once the horde is big enough
+const MAX_SCALE: f32 = 0.9;
+const ZOOM_PER_FOLLOWER: f32 = 0.015;
+const ZOOM_RATE: f32 = 2.;
+
+const FOLLOW_RATE: f32 = 6.;
+const SNAP_DISTANCE: f32 = 400.;
+/// How far ahead of the player the camera looks at full speed
+const LOOKAHEAD_DISTANCE: f32 = 80.;
+const LOOKAHEAD_RATE: f32 = 3.;
+
+/// Render layers only drawn by one viewport's camera, for per-player overlays
+pub const LEFT_VIEW_LAYER: u8 = 2;
+pub const RIGHT_VIEW_LAYER: u8 = 3;
+
+/// Smoothed follow state of a game camera
+#[derive(Component, Default, Debug)]
+pub struct CameraFollow {
+    /// Where the camera looks before effects like screen shake are added on top
+    pub center: Option<Vec2>,
+    pub last_position: Option<Vec2>,
+    pub lookahead: Vec2,
+}
 
 fn setup_game_camera(mut commands: Commands) {
     // Left Camera
@@ -29,7 +54,11 @@ fn setup_game_camera(mut commands: Commands) {
             projection: OrthographicProjection { far: 1000.0 / SCALE, scale: SCALE, ..default() },
             ..default()
         })
-        .insert(LeftCamera);
+        .insert(LeftCamera)
+        .insert(CameraFollow::default())
+        // Overlays like the HUD are children of the camera, and need a visible parent
+        .insert_bundle(VisibilityBundle::default())
+        .insert(RenderLayers::from_layers(&[0, LEFT_VIEW_LAYER]));
 
     // Right Camera
     commands
@@ -47,46 +76,94 @@ fn setup_game_camera(mut commands: Commands) {
             },
             ..default()
         })
-        .insert(RightCamera);
+        .insert(RightCamera)
+        .insert(CameraFollow::default())
+        .insert_bundle(VisibilityBundle::default())
+        .insert(RenderLayers::from_layers(&[0, RIGHT_VIEW_LAYER]));
 }
 
 pub fn camera_follow(
-    // mut cameras: ParamSet<(Query<&mut Transform, With<LeftCamera>)>,
-    player_query: Query<
-        &Transform,
-        (Without<LeftCamera>, Without<RightCamera>, Changed<Transform>, With<Player>),
+    time: Res<Time>,
+    map_settings: Res<MapSettings>,
+    player_query: Query<(&Player, &Transform), Without<CameraFollow>>,
+    mut cameras: Query<
+        (
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut CameraFollow,
+            &Camera,
+            Option<&RightCamera>,
+        ),
+        Without<MiniMapCamera>,
     >,
-    mut cameras: ParamSet<(
-        Query<&mut Transform, (With<LeftCamera>, Without<MiniMapCamera>)>,
-        Query<&mut Transform, (With<RightCamera>, Without<MiniMapCamera>)>,
-    )>,
 ) {
-    let players = player_query.iter().collect::<Vec<_>>();
-
-    // Left Cam
-    for mut t in cameras.p0().iter_mut() {
-        if let Some(player_transform) = players.get(0) {
-            let pos = player_transform.translation;
-            t.translation.x = pos.x;
-            t.translation.y = pos.y;
-        }
+    let dt = time.delta_seconds();
+    if dt <= 0. {
+        return;
     }
 
-    // Right Cam
-    for mut t in cameras.p1().iter_mut() {
-        if let Some(player_transform) = players.get(1) {
-            let pos = player_transform.translation;
-            t.translation.x = pos.x;
-            t.translation.y = pos.y;
+    for (mut t, mut projection, mut follow, camera, right) in cameras.iter_mut() {
+        // Left camera follows the first player, right camera the second
+        let handle = if right.is_some() { 1 } else { 0 };
+        let (player, player_transform) = match player_query.iter().find(|(p, _)| p.handle == handle)
+        {
+            Some(player) => player,
+            None => continue,
+        };
+
+        // Players have no velocity of their own, so measure it between frames
+        let position = player_transform.translation.xy();
+        let velocity = follow.last_position.map_or(Vec2::ZERO, |last| (position - last) / dt);
+        follow.last_position = Some(position);
+
+        let facing = (player_transform.rotation * Vec3::Y).xy();
+        let max_speed = player.movement_speed * player_settings::SPEED_MULTIPLIER;
+        let speed_ratio = (velocity.length() / max_speed).min(1.);
+        follow.lookahead = follow
+            .lookahead
+            .lerp(facing * LOOKAHEAD_DISTANCE * speed_ratio, damp(LOOKAHEAD_RATE, dt));
+
+        let horde = player.active_zombies.len() as f32;
+        let target_scale = (SCALE + horde * ZOOM_PER_FOLLOWER).min(MAX_SCALE);
+        projection.scale += (target_scale - projection.scale) * damp(ZOOM_RATE, dt);
+
+        // Respawns and teleporters move the player too far to pan after
+        let target = position + follow.lookahead;
+        let current = follow.center.unwrap_or_else(|| t.translation.xy());
+        let mut next = if current.distance(target) > SNAP_DISTANCE {
+            target
+        } else {
+            current.lerp(target, damp(FOLLOW_RATE, dt))
+        };
+
+        // Keep the view inside the map, centering on axes where the map is smaller than the view
+        if let Some(viewport) = camera.logical_viewport_size() {
+            let half_view = viewport * projection.scale / 2.;
+            let (min, max) = (map_settings.min() + half_view, map_settings.max() - half_view);
+            let center = map_settings.center();
+            next.x = if min.x < max.x { next.x.clamp(min.x, max.x) } else { center.x };
+            next.y = if min.y < max.y { next.y.clamp(min.y, max.y) } else { center.y };
         }
+
+        follow.center = Some(next);
+        t.translation.x = next.x;
+        t.translation.y = next.y;
     }
 }
 
+/// Frame rate independent factor for exponential smoothing at `rate` per second
+fn damp(rate: f32, dt: f32) -> f32 {
+    1. - (-rate * dt).exp()
+}
+
 fn update_camera_viewports(
     windows: Res<Windows>,
     mut resize_events: EventReader<WindowResized>,
-    mut right_camera: Query<&mut Camera, With<RightCamera>>,
-    mut left_camera: Query<&mut Camera, (With<LeftCamera>, Without<RightCamera>)>,
+    mut right_camera: Query<&mut Camera, (With<RightCamera>, Without<MiniMapCamera>)>,
+    mut left_camera: Query<
+        &mut Camera,
+        (With<LeftCamera>, Without<RightCamera>, Without<MiniMapCamera>),
+    >,
 ) {
     // We need to dynamically resize the camera's viewports whenever the window size changes
     // so then each camera always takes up half the screen.
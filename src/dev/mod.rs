@@ -0,0 +1,17 @@
+// This is the folder for developer tooling, only built with the `dev` feature
+
+use crate::prelude::*;
+use bevy_egui::EguiPlugin;
+
+mod overlay;
+mod tuning;
+
+pub use overlay::*;
+pub use tuning::*;
+
+pub struct DevPlugin;
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EguiPlugin).add_plugin(FlockingTuningPlugin).add_plugin(DebugOverlayPlugin);
+    }
+}
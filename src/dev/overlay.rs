@@ -0,0 +1,256 @@
+use crate::dev::*;
+use bevy::math::Vec3Swizzles;
+use bevy_egui::{
+    egui::{self, Align2, Color32, FontId, Pos2, Stroke},
+    EguiContext,
+};
+
+pub mod overlay_settings {
+    use bevy::prelude::KeyCode;
+
+    pub const GRID_KEY: KeyCode = KeyCode::F1;
+    pub const DIRECTIONS_KEY: KeyCode = KeyCode::F2;
+    pub const FORCES_KEY: KeyCode = KeyCode::F3;
+    pub const FOLLOW_RADII_KEY: KeyCode = KeyCode::F4;
+    pub const VISION_KEY: KeyCode = KeyCode::F5;
+    pub const TARGETS_KEY: KeyCode = KeyCode::F6;
+
+    /// Creatures per cell that paint it fully opaque
+    pub const FULL_CELL: usize = 8;
+    pub const DIRECTION_LENGTH: f32 = 20.; // world units
+    /// World units of arrow per unit of force weight
+    pub const FORCE_SCALE: f32 = 2.;
+    pub const LINE_WIDTH: f32 = 1.;
+}
+
+/// Which debug layers are drawn over the game views
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DebugOverlay {
+    pub grid: bool,
+    pub directions: bool,
+    pub forces: bool,
+    pub follow_radii: bool,
+    pub vision: bool,
+    pub targets: bool,
+}
+
+fn color32(color: Color, alpha: f32) -> Color32 {
+    let [r, g, b, _] = color.as_rgba_f32();
+    Color32::from_rgba_unmultiplied(
+        (r * 255.) as u8,
+        (g * 255.) as u8,
+        (b * 255.) as u8,
+        (alpha * 255.) as u8,
+    )
+}
+
+pub fn toggle_debug_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    let overlay = &mut *overlay;
+    for (key, layer) in [
+        (overlay_settings::GRID_KEY, &mut overlay.grid),
+        (overlay_settings::DIRECTIONS_KEY, &mut overlay.directions),
+        (overlay_settings::FORCES_KEY, &mut overlay.forces),
+        (overlay_settings::FOLLOW_RADII_KEY, &mut overlay.follow_radii),
+        (overlay_settings::VISION_KEY, &mut overlay.vision),
+        (overlay_settings::TARGETS_KEY, &mut overlay.targets),
+    ] {
+        if keys.just_pressed(key) {
+            *layer = !*layer;
+        }
+    }
+}
+
+pub fn debug_overlay_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    egui::Window::new("Debug Overlay").show(egui_context.ctx_mut(), |ui| {
+        // Same trick as the flocking panel, only touch the resource on a change
+        let mut layers = overlay.clone();
+
+        ui.checkbox(&mut layers.grid, "Cache grid (F1)");
+        ui.checkbox(&mut layers.directions, "Directions (F2)");
+        ui.checkbox(&mut layers.forces, "Flocking forces (F3)");
+        ui.checkbox(&mut layers.follow_radii, "Follow radii (F4)");
+        ui.checkbox(&mut layers.vision, "Vision (F5)");
+        ui.checkbox(&mut layers.targets, "Target links (F6)");
+
+        ui.separator();
+        ui.label("Cohesion: green, Alignment: blue, Separation: yellow, Avoidance: red");
+
+        if layers != *overlay {
+            *overlay = layers;
+        }
+    });
+}
+
+/// Paints the enabled layers on top of each game viewport, in egui's background layer
+pub fn draw_debug_overlay(
+    mut egui_context: ResMut<EguiContext>,
+    overlay: Res<DebugOverlay>,
+    windows: Res<Windows>,
+    settings: Res<FlockingSettings>,
+    cache_grid: Option<Res<CacheGrid>>,
+    cameras: Query<
+        (&Camera, &GlobalTransform, &OrthographicProjection),
+        (With<CameraFollow>, Without<MiniMapCamera>),
+    >,
+    players: Query<(Entity, &Player, &Transform)>,
+    creatures: FlockingCreatures,
+    behaviours: Query<(Option<&CreatureFollow>, Option<&CreatureTarget>)>,
+    transforms: Query<&Transform>,
+) {
+    if *overlay == DebugOverlay::default() {
+        return;
+    }
+
+    let scale_factor = windows.primary().scale_factor() as f32;
+    let painter = egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("debug_overlay")));
+    let stroke =
+        |color: Color, alpha: f32| Stroke::new(overlay_settings::LINE_WIDTH, color32(color, alpha));
+
+    for (camera, camera_transform, projection) in cameras.iter() {
+        let size = match camera.logical_viewport_size() {
+            Some(size) => size,
+            None => continue,
+        };
+        let origin = camera
+            .viewport
+            .as_ref()
+            .map_or(Vec2::ZERO, |v| v.physical_position.as_vec2() / scale_factor);
+
+        // Viewport coordinates grow upwards, egui's grow downwards
+        let to_screen = |world: Vec2| {
+            camera
+                .world_to_viewport(camera_transform, world.extend(0.))
+                .map(|p| Pos2::new(origin.x + p.x, origin.y + size.y - p.y))
+        };
+        let to_length = |world: f32| world / projection.scale;
+
+        let clip =
+            egui::Rect::from_min_size(Pos2::new(origin.x, origin.y), egui::vec2(size.x, size.y));
+        let painter = painter.with_clip_rect(clip);
+
+        if let Some(cache_grid) = cache_grid.as_ref().filter(|_| overlay.grid) {
+            for ((i, j), entities) in cache_grid.grid.iter() {
+                let (min, max) = CacheGrid::cell_rect(*i, *j);
+                let (a, b) = match (to_screen(min), to_screen(max)) {
+                    (Some(a), Some(b)) => (a, b),
+                    _ => continue,
+                };
+                let rect = egui::Rect::from_two_pos(a, b);
+                let occupancy =
+                    (entities.len() as f32 / overlay_settings::FULL_CELL as f32).min(1.);
+
+                painter.rect_filled(rect, 0., color32(Color::ORANGE, 0.1 + 0.4 * occupancy));
+                painter.rect_stroke(rect, 0., stroke(Color::ORANGE, 0.6));
+                painter.text(
+                    rect.center(),
+                    Align2::CENTER_CENTER,
+                    entities.len().to_string(),
+                    FontId::monospace(10.),
+                    Color32::WHITE,
+                );
+            }
+        }
+
+        if overlay.follow_radii {
+            for (_, player, transform) in players.iter() {
+                if let Some(center) = to_screen(transform.translation.xy()) {
+                    painter.circle_stroke(
+                        center,
+                        to_length(player_settings::FOLLOW_COLLECTION_DISTANCE),
+                        stroke(player.color, 0.8),
+                    );
+                }
+            }
+        }
+
+        for (entity, direction, transform, c_type, size) in creatures.iter() {
+            let position = transform.translation.xy();
+            let center = match to_screen(position) {
+                Some(center) => center,
+                None => continue,
+            };
+            let (follow, target) = behaviours.get(entity).unwrap_or((None, None));
+
+            if overlay.directions {
+                let tip = position + direction.0 * overlay_settings::DIRECTION_LENGTH;
+                if let Some(tip) = to_screen(tip) {
+                    painter.arrow(center, tip - center, stroke(Color::WHITE, 0.9));
+                }
+            }
+
+            if overlay.vision {
+                painter.circle_stroke(center, to_length(settings.vision), stroke(Color::CYAN, 0.3));
+                painter.circle_stroke(
+                    center,
+                    to_length(settings.vision / 2.),
+                    stroke(Color::CYAN, 0.15),
+                );
+            }
+
+            // Where the follower stops around its owner
+            if let (true, Some(follow), Some(owner)) = (overlay.follow_radii, follow, c_type.0) {
+                if let Ok((_, player, owner_transform)) = players.get(owner) {
+                    if let Some(owner_center) = to_screen(owner_transform.translation.xy()) {
+                        painter.circle_stroke(
+                            owner_center,
+                            to_length(follow.0),
+                            stroke(player.color, 0.15),
+                        );
+                    }
+                }
+            }
+
+            if let (true, Some(target)) = (overlay.targets, target) {
+                let end = transforms.get(target.0).ok().and_then(|t| to_screen(t.translation.xy()));
+                if let Some(end) = end {
+                    painter.line_segment([center, end], stroke(Color::RED, 0.8));
+                }
+            }
+
+            if let Some(cache_grid) = cache_grid.as_ref().filter(|_| overlay.forces) {
+                let forces = flocking_forces(
+                    entity, position, c_type, size.0, &creatures, cache_grid, &settings,
+                );
+
+                let arrows = forces
+                    .avoidance
+                    .iter()
+                    .map(|f| (*f, settings.collision_avoidance, Color::RED))
+                    .chain(forces.cohesion.map(|f| (f, settings.cohesion, Color::GREEN)))
+                    .chain(forces.alignment.map(|f| (f, settings.alignment, Color::BLUE)))
+                    .chain(forces.separation.map(|f| (f, settings.separation, Color::YELLOW)));
+
+                for (force, weight, color) in arrows {
+                    if force.is_nan() {
+                        continue;
+                    }
+                    let tip = position + force * weight * overlay_settings::FORCE_SCALE;
+                    if let Some(tip) = to_screen(tip) {
+                        painter.arrow(center, tip - center, stroke(color, 0.9));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct DebugOverlayPlugin;
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>();
+
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::InGame)
+                .with_system(toggle_debug_overlay)
+                .with_system(debug_overlay_panel)
+                .with_system(draw_debug_overlay)
+                .into(),
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::dev::*;
+use bevy_egui::{egui, EguiContext};
+
+pub fn flocking_tuning_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut settings: ResMut<FlockingSettings>,
+    mut status: Local<Option<String>>,
+) {
+    egui::Window::new("Flocking").show(egui_context.ctx_mut(), |ui| {
+        // Only hand out a mutable borrow when a value actually changed,
+        // so `Res<FlockingSettings>::is_changed` stays meaningful.
+        let mut tuned = settings.clone();
+
+        ui.add(
+            egui::Slider::new(&mut tuned.collision_avoidance, 0.0..=50.0)
+                .text("Collision Avoidance"),
+        );
+        ui.add(egui::Slider::new(&mut tuned.cohesion, 0.0..=50.0).text("Cohesion"));
+        ui.add(egui::Slider::new(&mut tuned.separation, 0.0..=50.0).text("Separation"));
+        ui.add(egui::Slider::new(&mut tuned.alignment, 0.0..=50.0).text("Alignment"));
+        ui.add(egui::Slider::new(&mut tuned.chase, 0.0..=50.0).text("Chase"));
+        ui.add(egui::Slider::new(&mut tuned.vision, 10.0..=300.0).text("Vision"));
+
+        ui.separator();
+        ui.label("Presets");
+        ui.horizontal_wrapped(|ui| {
+            for (name, preset) in FlockingSettings::presets() {
+                if ui.button(name).clicked() {
+                    tuned = preset;
+                }
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Export").clicked() {
+                *status = Some(match tuned.export() {
+                    Ok(_) => format!("Saved to {}", FLOCKING_PRESET_PATH),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+            }
+            if ui.button("Reload").clicked() {
+                tuned = FlockingSettings::load();
+                *status = Some(format!("Loaded {}", FLOCKING_PRESET_PATH));
+            }
+        });
+
+        if let Some(status) = &*status {
+            ui.label(status);
+        }
+
+        if tuned != *settings {
+            *settings = tuned;
+        }
+    });
+}
+
+pub struct FlockingTuningPlugin;
+impl Plugin for FlockingTuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(flocking_tuning_panel.run_in_state(AppState::InGame));
+    }
+}
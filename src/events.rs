@@ -11,11 +11,76 @@ pub struct DamageEvent {
 
 impl_new!(DamageEvent, victim: Entity, attacker: Entity);
 
+#[derive(Debug)]
+pub struct BossKilledEvent {
+    pub killer: Entity,
+    pub position: Vec2,
+}
+
+/// What finished off a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillCause {
+    /// Handle of the player who fired, which can be the victim's own
+    Player(usize),
+    Zombies,
+    Boss,
+    /// Hazard tiles and anything else in the map
+    Hazard,
+}
+
+/// Things worth telling the players about, for the kill feed, sounds, stats or logs.
+/// Players are referred to by handle, which outlives their entity across respawns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundEvent {
+    PlayerKilled {
+        victim: usize,
+        cause: KillCause,
+    },
+    HordeStolen {
+        from: usize,
+        by: usize,
+        count: usize,
+    },
+    BossSpawned,
+    BossKilled {
+        killer: Option<usize>,
+    },
+    /// Horde size crossed one of the `kill_feed_settings::MILESTONES`
+    MilestoneReached {
+        player: usize,
+        horde: usize,
+    },
+    LeadChanged {
+        leader: usize,
+    },
+}
+
+/// A zombie or boss died, `killer` is the handle of the player whose hit took it to zero health
+#[derive(Debug)]
+pub struct CreatureKilledEvent {
+    pub killer: Option<usize>,
+}
+
+/// A bullet fired by `shooter` hit a player, a creature or a barrel
+#[derive(Debug)]
+pub struct BulletHitEvent {
+    pub shooter: Entity,
+}
+
+/// A bullet hit the solid tile at `tile` (tilemap coordinates)
+#[derive(Debug)]
+pub struct StructureDamageEvent {
+    pub tile: (i32, i32),
+    pub damage: u32,
+    pub attacker: Entity,
+}
+
 #[derive(Component, Debug, Default, Eq, PartialEq)]
 pub enum SpawnType {
     #[default]
     Player,
     Zombie,
+    Boss,
 }
 
 #[derive(Debug, Default)]
@@ -23,4 +88,6 @@ pub struct SpawnEvent {
     pub handle: Option<usize>,
     pub color: Option<Color>,
     pub spawn_type: SpawnType,
+    /// Picked from the map's spawn points when not given
+    pub position: Option<Vec2>,
 }
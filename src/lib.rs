@@ -3,12 +3,15 @@
 
 mod camera;
 mod components;
+#[cfg(feature = "dev")]
+mod dev;
 mod events;
 mod loading;
 mod map;
 mod menu;
 mod random;
 mod round;
+mod settings;
 mod state;
 mod utils;
 
@@ -34,6 +37,7 @@ mod prelude {
     pub use crate::menu::*;
     pub use crate::random::*;
     pub use crate::round::*;
+    pub use crate::settings::*;
     pub use crate::state::*;
     pub use crate::utils::*;
 
@@ -49,28 +53,40 @@ pub const LAUNCHER_TITLE: &str = "Brain hoarders";
 
 pub fn app() -> App {
     let mut app = App::new();
+    let settings = GameSettings::load().unwrap_or_else(|e| {
+        app.insert_resource(SettingsLoadError(e.to_string()));
+        GameSettings::default()
+    });
 
     app.insert_resource(WindowDescriptor {
         fit_canvas_to_parent: true,
         title: LAUNCHER_TITLE.to_string(),
         canvas: Some("#bevy".to_string()),
-        width: SCREEN_WIDTH as f32 * 10.0,
-        height: SCREEN_HEIGHT as f32 * 10.0,
+        width: settings.resolution.0,
+        height: settings.resolution.1,
+        mode: settings.display_mode.window_mode(),
+        present_mode: settings.present_mode(),
         ..Default::default()
     })
+    .insert_resource(settings)
     .insert_resource(ImageSettings::default_nearest())
     .insert_resource(ClearColor(Color::hex("171717").unwrap()));
 
     app.add_loopless_state(AppState::AssetLoading)
+        .add_loopless_state(InGameState::Playing)
         .add_plugins(DefaultPlugins)
         .add_plugin(TilemapPlugin)
         .add_plugin(TiledMapPlugin)
         .add_plugin(CameraPlugin)
+        .add_plugin(SettingsPlugin)
         .add_plugin(LoadingPlugin)
         .add_plugin(AudioPlugin)
         .add_plugins(MenuPlugins)
         .add_plugin(MapPlugin)
         .add_plugin(RoundPlugin);
 
+    #[cfg(feature = "dev")]
+    app.add_plugin(dev::DevPlugin);
+
     app
 }
@@ -48,8 +48,8 @@ pub struct AudioAssets {
 
 #[derive(AssetCollection)]
 pub struct MapAssets {
-    #[asset(path = "maps/map.tmx")]
-    pub map: Handle<TiledMap>,
+    #[asset(path = "maps/maps.catalogue.ron")]
+    pub catalogue: Handle<MapCatalogue>,
 }
 
 #[derive(AssetCollection)]
@@ -8,17 +8,33 @@ pub const CHUNK_RESOLUTION: usize = 20;
 
 #[derive(Debug, Default)]
 pub struct CacheGrid {
-    pub grid: HashMap<(i8, i8), HashSet<Entity>>,
-    pub associations: HashMap<Entity, (i8, i8)>,
+    pub grid: HashMap<(i16, i16), HashSet<Entity>>,
+    pub associations: HashMap<Entity, (i16, i16)>,
 }
 
 impl CacheGrid {
-    pub fn update_entity(&mut self, entity: Entity, pos: Vec2) {
-        let x = pos.x;
-        let y = pos.y;
+    /// (row, column) of the cell holding `pos`. Truncated towards zero, so the cells on either
+    /// side of an axis share index 0 and make it twice as wide
+    pub fn cell_of(pos: Vec2) -> (i16, i16) {
+        let cell = pos / CHUNK_RESOLUTION as f32;
+        (cell.y as i16, cell.x as i16)
+    }
+
+    /// World space min and max corners of a cell, matching `cell_of`
+    pub fn cell_rect(i: i16, j: i16) -> (Vec2, Vec2) {
+        let bounds = |index: i16| {
+            let index = index as f32;
+            let min = if index > 0. { index } else { index - 1. };
+            let max = if index < 0. { index } else { index + 1. };
+            (min * CHUNK_RESOLUTION as f32, max * CHUNK_RESOLUTION as f32)
+        };
+
+        let ((x_min, x_max), (y_min, y_max)) = (bounds(j), bounds(i));
+        (Vec2::new(x_min, y_min), Vec2::new(x_max, y_max))
+    }
 
-        let i = (y / CHUNK_RESOLUTION as f32) as i8;
-        let j = (x / CHUNK_RESOLUTION as f32) as i8;
+    pub fn update_entity(&mut self, entity: Entity, pos: Vec2) {
+        let (i, j) = Self::cell_of(pos);
 
         if let Some((old_i, old_j)) = self.associations.get(&entity) {
             let old_i = *old_i;
@@ -42,16 +58,10 @@ impl CacheGrid {
     pub fn get_nearby_entities(&self, position: Vec2, radius: f32) -> Vec<Entity> {
         let mut result = vec![];
 
-        let x = position.x;
-        let y = position.y;
-
-        let x_begin = x - radius;
-        let y_begin = y - radius;
-        let i_begin = (y_begin / CHUNK_RESOLUTION as f32) as i8;
-        let j_begin = (x_begin / CHUNK_RESOLUTION as f32) as i8;
+        let (i_begin, j_begin) = Self::cell_of(position - radius);
 
-        let i_to = (radius * 2.0 / CHUNK_RESOLUTION as f32).ceil() as i8;
-        let j_to = (radius * 2.0 / CHUNK_RESOLUTION as f32).ceil() as i8;
+        let i_to = (radius * 2.0 / CHUNK_RESOLUTION as f32).ceil() as i16;
+        let j_to = (radius * 2.0 / CHUNK_RESOLUTION as f32).ceil() as i16;
 
         let i_end = i_begin + i_to;
         let j_end = j_begin + j_to;
@@ -0,0 +1,66 @@
+use crate::map::*;
+use bevy::{
+    asset::{AssetLoader, LoadedAsset},
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum MapSource {
+    /// Path of the .tmx file, relative to the assets folder
+    Tiled(String),
+    /// Built procedurally when the round starts
+    Generated(ArenaSettings),
+}
+
+/// One entry of the map manifest, `assets/maps/maps.catalogue.ron`
+#[derive(Debug, Clone, Deserialize)]
+pub struct MapEntry {
+    pub name: String,
+    pub source: MapSource,
+    /// Path of the preview image, relative to the assets folder
+    pub preview: String,
+    pub recommended_players: u32,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "3b1a5c0e-4f0d-4c6b-9f1e-7d2a8e6b1c55"]
+pub struct MapCatalogue {
+    pub maps: Vec<MapEntry>,
+}
+
+pub struct MapCatalogueLoader;
+
+impl AssetLoader for MapCatalogueLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, anyhow::Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let catalogue: MapCatalogue = ron::de::from_bytes(bytes)?;
+            if catalogue.maps.is_empty() {
+                anyhow::bail!("{:?} does not list any maps", load_context.path());
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(catalogue));
+            Ok(())
+        })
+    }
+
+    /// Plain `.ron` is left free for other assets
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["catalogue.ron"];
+        EXTENSIONS
+    }
+}
+
+/// The map picked on the map selection screen, used by `WorldGen` and the post-match screen
+#[derive(Debug, Clone)]
+pub struct SelectedMap {
+    pub entry: MapEntry,
+    /// Only set for Tiled maps
+    pub map: Option<Handle<TiledMap>>,
+    /// Arena seed, resolved once on selection so restarts and rematches rebuild the same arena
+    pub seed: u64,
+}
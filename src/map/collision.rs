@@ -0,0 +1,174 @@
+use crate::map::*;
+use bevy::utils::HashSet;
+
+/// Layers with this name (or a `collision` bool property) mark every placed tile as solid
+pub const COLLISION_LAYER_NAME: &str = "collision";
+/// Tileset tiles with this bool property are solid wherever they are placed
+pub const COLLISION_PROPERTY: &str = "collision";
+
+/// Global ids of tiles that are solid because of tileset data
+pub fn solid_tiles(map: &::tiled::Map) -> HashSet<u32> {
+    let mut solid_gids = HashSet::default();
+    for tileset in map.tilesets.iter() {
+        for tile in tileset.tiles.iter() {
+            let has_shapes = tile.objectgroup.as_ref().map_or(false, |g| !g.objects.is_empty());
+            if has_shapes
+                || is_flagged(&tile.properties, COLLISION_PROPERTY)
+                || tile.properties.contains_key(DESTRUCTIBLE_HP_PROPERTY)
+            {
+                solid_gids.insert(tileset.first_gid + tile.id);
+            }
+        }
+    }
+    solid_gids
+}
+
+pub fn is_collision_layer(layer: &::tiled::Layer) -> bool {
+    layer.name.eq_ignore_ascii_case(COLLISION_LAYER_NAME)
+        || is_flagged(&layer.properties, COLLISION_PROPERTY)
+}
+
+/// Solid tiles of the current map, indexed by tilemap position (y = 0 is the bottom row)
+#[derive(Debug, Default)]
+pub struct CollisionMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: Vec2,
+    /// World position of the bottom left corner of tile (0, 0)
+    pub origin: Vec2,
+    /// Number of layers with a wall on each tile, hidden ones included
+    pub walls: Vec<u8>,
+    /// Number of closed doors on each tile, kept apart so opening one never clears a wall
+    pub blockers: Vec<u8>,
+}
+
+impl CollisionMap {
+    pub fn from_tiled(map: &::tiled::Map, settings: &MapSettings) -> Self {
+        let (width, height) = (map.width, map.height);
+        let (tile_size, origin) = (settings.tile_size, settings.origin);
+        let mut walls = vec![0u8; (width * height) as usize];
+
+        let solid_gids = solid_tiles(map);
+
+        for layer in map.layers.iter() {
+            let collision_layer = is_collision_layer(layer);
+
+            let tiles = match &layer.tiles {
+                ::tiled::LayerData::Finite(tiles) => tiles,
+                _ => continue,
+            };
+
+            for (row, row_tiles) in tiles.iter().enumerate() {
+                for (x, tile) in row_tiles.iter().enumerate() {
+                    if tile.gid == 0 || !(collision_layer || solid_gids.contains(&tile.gid)) {
+                        continue;
+                    }
+
+                    // Tiled rows go top-down, tilemap rows go bottom-up
+                    let y = height as usize - 1 - row;
+                    let walls = &mut walls[y * width as usize + x];
+                    *walls = walls.saturating_add(1);
+                }
+            }
+        }
+
+        let blockers = vec![0; walls.len()];
+        Self { width, height, tile_size, origin, walls, blockers }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        Some(y as usize * self.width as usize + x as usize)
+    }
+
+    pub fn is_solid_tile(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).map_or(false, |i| self.walls[i] > 0 || self.blockers[i] > 0)
+    }
+
+    /// One of the layers lost its wall on this tile, the others keep it solid
+    pub fn remove_wall(&mut self, x: i32, y: i32) {
+        if let Some(i) = self.index(x, y) {
+            self.walls[i] = self.walls[i].saturating_sub(1);
+        }
+    }
+
+    /// Tiles under the rect between `min` and `max`
+    pub fn tiles_in_rect(&self, min: Vec2, max: Vec2) -> Vec<(i32, i32)> {
+        // Tiles only touched by the edge of the rect are left out
+        let (x_begin, y_begin) = self.world_to_tile(min + 0.5);
+        let (x_end, y_end) = self.world_to_tile(max - 0.5);
+
+        (y_begin..=y_end).flat_map(|y| (x_begin..=x_end).map(move |x| (x, y))).collect()
+    }
+
+    /// Block or unblock tiles on top of whatever walls they already have
+    pub fn set_blocked(&mut self, tiles: &[(i32, i32)], blocked: bool) {
+        for (x, y) in tiles {
+            if let Some(i) = self.index(*x, *y) {
+                self.blockers[i] = if blocked {
+                    self.blockers[i].saturating_add(1)
+                } else {
+                    self.blockers[i].saturating_sub(1)
+                };
+            }
+        }
+    }
+
+    pub fn world_to_tile(&self, pos: Vec2) -> (i32, i32) {
+        let local = (pos - self.origin) / self.tile_size;
+        (local.x.floor() as i32, local.y.floor() as i32)
+    }
+
+    pub fn is_solid(&self, pos: Vec2) -> bool {
+        let (x, y) = self.world_to_tile(pos);
+        self.is_solid_tile(x, y)
+    }
+
+    /// Does a circle at `pos` overlap any solid tile?
+    pub fn collides(&self, pos: Vec2, radius: f32) -> bool {
+        let (x_begin, y_begin) = self.world_to_tile(pos - Vec2::splat(radius));
+        let (x_end, y_end) = self.world_to_tile(pos + Vec2::splat(radius));
+
+        for y in y_begin..=y_end {
+            for x in x_begin..=x_end {
+                if !self.is_solid_tile(x, y) {
+                    continue;
+                }
+
+                let min = self.origin + Vec2::new(x as f32, y as f32) * self.tile_size;
+                let closest = pos.clamp(min, min + self.tile_size);
+                if closest.distance_squared(pos) < radius * radius {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Move a circle from `from` towards `to`, sliding along walls instead of stopping dead
+    pub fn slide(&self, from: Vec2, to: Vec2, radius: f32) -> Vec2 {
+        // Let anything that ended up inside a wall walk back out
+        if !self.collides(to, radius) || self.collides(from, radius) {
+            return to;
+        }
+
+        let x_only = Vec2::new(to.x, from.y);
+        if !self.collides(x_only, radius) {
+            return x_only;
+        }
+
+        let y_only = Vec2::new(from.x, to.y);
+        if !self.collides(y_only, radius) {
+            return y_only;
+        }
+
+        from
+    }
+}
+
+fn is_flagged(properties: &::tiled::Properties, name: &str) -> bool {
+    matches!(properties.get(name), Some(::tiled::PropertyValue::BoolValue(true)))
+}
@@ -0,0 +1,84 @@
+use crate::map::*;
+use bevy::utils::HashMap;
+
+/// Tileset tiles with this int property are destructible, with that many hit points
+pub const DESTRUCTIBLE_HP_PROPERTY: &str = "hp";
+/// Optional int property: id of the tile (in the same tileset) shown once at half health
+pub const DAMAGED_TILE_PROPERTY: &str = "damaged_tile";
+
+#[derive(Component, Debug, Clone)]
+pub struct Destructible {
+    pub health: u32,
+    pub max_health: u32,
+    /// Texture index shown once the structure is down to half health
+    pub damaged_texture: Option<u32>,
+}
+
+/// Destructible tiles defined in the tilesets, keyed by global id
+pub fn destructible_tiles(map: &::tiled::Map) -> HashMap<u32, Destructible> {
+    let mut destructibles = HashMap::default();
+    for tileset in map.tilesets.iter() {
+        for tile in tileset.tiles.iter() {
+            let health = match int_property(&tile.properties, DESTRUCTIBLE_HP_PROPERTY) {
+                Some(hp) if hp > 0 => hp as u32,
+                _ => continue,
+            };
+
+            let damaged_texture =
+                int_property(&tile.properties, DAMAGED_TILE_PROPERTY).map(|id| id as u32);
+            destructibles.insert(
+                tileset.first_gid + tile.id,
+                Destructible { health, max_health: health, damaged_texture },
+            );
+        }
+    }
+    destructibles
+}
+
+pub fn int_property(properties: &::tiled::Properties, name: &str) -> Option<i32> {
+    match properties.get(name) {
+        Some(::tiled::PropertyValue::IntValue(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+pub fn damage_structures(
+    mut commands: Commands,
+    mut events: EventReader<StructureDamageEvent>,
+    mut collision_map: ResMut<CollisionMap>,
+    mut layers: Query<&mut TileStorage>,
+    mut tiles: Query<(&mut Destructible, &mut TileTexture)>,
+) {
+    for event in events.iter() {
+        let (x, y) = event.tile;
+        // Out of bounds, or already knocked down this frame
+        if !collision_map.is_solid_tile(x, y) {
+            continue;
+        }
+
+        let tile_pos = TilePos { x: x as u32, y: y as u32 };
+
+        for mut storage in &mut layers {
+            let tile = match storage.get(&tile_pos) {
+                Some(tile) => tile,
+                None => continue,
+            };
+            let (mut destructible, mut texture) = match tiles.get_mut(tile) {
+                Ok(destructible) => destructible,
+                Err(_) => continue,
+            };
+
+            destructible.health = destructible.health.saturating_sub(event.damage);
+            if destructible.health == 0 {
+                commands.entity(tile).despawn_recursive();
+                storage.set(&tile_pos, None);
+                // Walls on other layers, hidden ones included, keep the tile solid
+                collision_map.remove_wall(x, y);
+            } else if destructible.health * 2 <= destructible.max_health {
+                if let Some(damaged_texture) = destructible.damaged_texture {
+                    texture.0 = damaged_texture;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::map::*;
+use serde::Deserialize;
+use std::collections::VecDeque;
+
+/// Generated arenas are drawn with a single tileset
+pub const ARENA_TILESET: &str = "maps/tiles/roguelikeCity.png";
+const ARENA_TILE_SIZE: f32 = MAP_TILE_SIZE as f32;
+const ARENA_TILE_SPACING: f32 = 1.;
+
+// Tile indices into `ARENA_TILESET`
+const GROUND_TILE: u32 = 973;
+const ROAD_TILE: u32 = 714;
+const RUINS_TILE: u32 = 963;
+const OBSTACLE_TILE: u32 = 637;
+
+const ROAD_SPEED: TerrainModifier = TerrainModifier { speed_multiplier: 1.3, slows_zombies: false };
+const RUINS_SPEED: TerrainModifier = TerrainModifier { speed_multiplier: 0.7, slows_zombies: true };
+
+const ROAD_WIDTH: u32 = 3;
+const ROAD_PAIRS: usize = 2;
+const RUIN_COUNT: usize = 8;
+const RUIN_WALL_CHANCE: f64 = 0.6;
+const RUBBLE_COUNT: usize = 40;
+/// Distance from the map edge to the player spawns, in tiles
+const SPAWN_MARGIN: u32 = 10;
+/// Radius kept free of obstacles around each player spawn, in tiles
+const SPAWN_CLEARANCE: u32 = 4;
+
+/// Options for a generated map entry in the map catalogue
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ArenaSettings {
+    /// In tiles
+    pub width: u32,
+    /// In tiles
+    pub height: u32,
+    /// Fixed seed so every peer builds the same arena. A new seed is rolled when not given.
+    pub seed: Option<u64>,
+}
+
+impl Default for ArenaSettings {
+    fn default() -> Self {
+        Self { width: 96, height: 96, seed: None }
+    }
+}
+
+pub struct ArenaLayer {
+    pub name: &'static str,
+    pub tiles: Vec<Option<u32>>,
+}
+
+/// A procedurally generated arena, indexed like the tilemap (y = 0 is the bottom row).
+/// The layout is point symmetric around the center so no player spawn is favoured.
+pub struct Arena {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<ArenaLayer>,
+    pub solid: Vec<bool>,
+    pub terrain: Vec<TerrainModifier>,
+    pub player_spawns: Vec<UVec2>,
+}
+
+impl Arena {
+    pub fn generate(settings: &ArenaSettings, seed: u64) -> Self {
+        let rng = RandomNumbers::seeded(seed);
+        let (width, height) =
+            (settings.width.max(SPAWN_MARGIN * 4), settings.height.max(SPAWN_MARGIN * 4));
+        let mut grid = ArenaGrid::new(width, height);
+
+        grid.place_roads(&rng);
+        grid.place_ruins(&rng);
+        grid.place_rubble(&rng);
+
+        // Spawns in the bottom half; the mirror gives the other two
+        let spawns = [
+            UVec2::new(SPAWN_MARGIN, SPAWN_MARGIN),
+            UVec2::new(width - 1 - SPAWN_MARGIN, SPAWN_MARGIN),
+        ];
+        for spawn in spawns {
+            grid.clear_around(spawn, SPAWN_CLEARANCE);
+            grid.carve_to_road(spawn);
+        }
+
+        grid.mirror();
+        grid.fill_unreachable(spawns[0]);
+
+        let player_spawns =
+            vec![spawns[0], grid.mirrored(spawns[0]), spawns[1], grid.mirrored(spawns[1])];
+
+        let ArenaGrid { road, ruins, solid, .. } = grid;
+        let terrain = road
+            .iter()
+            .zip(ruins.iter())
+            .map(|(road, ruins)| match (road, ruins) {
+                (_, true) => RUINS_SPEED,
+                (true, false) => ROAD_SPEED,
+                _ => TerrainModifier::default(),
+            })
+            .collect();
+
+        let layer = |name, flags: &[bool], tile| ArenaLayer {
+            name,
+            tiles: flags.iter().map(|f| f.then_some(tile)).collect(),
+        };
+        let layers = vec![
+            ArenaLayer { name: "ground", tiles: vec![Some(GROUND_TILE); solid.len()] },
+            layer("road", &road, ROAD_TILE),
+            layer("ruins", &ruins, RUINS_TILE),
+            layer("obstacles", &solid, OBSTACLE_TILE),
+        ];
+
+        Self { width, height, layers, solid, terrain, player_spawns }
+    }
+
+    pub fn map_settings(&self) -> MapSettings {
+        MapSettings::from_grid(self.width, self.height, Vec2::splat(ARENA_TILE_SIZE))
+    }
+
+    pub fn collision_map(&self, settings: &MapSettings) -> CollisionMap {
+        CollisionMap {
+            width: self.width,
+            height: self.height,
+            tile_size: settings.tile_size,
+            origin: settings.origin,
+            walls: self.solid.iter().map(|solid| *solid as u8).collect(),
+            blockers: vec![0; self.solid.len()],
+        }
+    }
+
+    pub fn terrain_map(&self, settings: &MapSettings) -> TerrainMap {
+        TerrainMap {
+            width: self.width,
+            height: self.height,
+            tile_size: settings.tile_size,
+            origin: settings.origin,
+            modifiers: self.terrain.clone(),
+        }
+    }
+
+    pub fn spawn_points(&self, settings: &MapSettings) -> SpawnPoints {
+        let mut spawns = SpawnPoints::default();
+        let clearance = settings.tile_size * SPAWN_CLEARANCE as f32;
+
+        for (i, tile) in self.player_spawns.iter().enumerate() {
+            let point = settings.origin + (tile.as_vec2() + 0.5) * settings.tile_size;
+            spawns.players.push((format!("spawn_{}", i + 1), point));
+            // Keep zombies from spawning on top of players
+            spawns.no_spawn.push(SpawnRect { min: point - clearance, max: point + clearance });
+        }
+
+        spawns
+    }
+
+    /// Spawn every layer of the arena into `bevy_ecs_tilemap` storage
+    pub fn spawn(&self, commands: &mut Commands, texture: Handle<Image>, settings: &MapSettings) {
+        let map_size = TilemapSize { x: self.width, y: self.height };
+        let tile_size = TilemapTileSize { x: ARENA_TILE_SIZE, y: ARENA_TILE_SIZE };
+        let grid_size = TilemapGridSize { x: ARENA_TILE_SIZE, y: ARENA_TILE_SIZE };
+
+        for (z, layer) in self.layers.iter().enumerate() {
+            let mut tile_storage = TileStorage::empty(map_size);
+            let layer_entity = commands.spawn().id();
+
+            for (i, tile) in layer.tiles.iter().enumerate() {
+                let tile_id = match tile {
+                    Some(tile_id) => *tile_id,
+                    None => continue,
+                };
+
+                let tile_pos = TilePos { x: i as u32 % self.width, y: i as u32 / self.width };
+                let tile_entity = commands
+                    .spawn()
+                    .insert_bundle(TileBundle {
+                        position: tile_pos,
+                        tilemap_id: TilemapId(layer_entity),
+                        texture: TileTexture(tile_id),
+                        ..Default::default()
+                    })
+                    .insert(RoundEntity)
+                    .id();
+                tile_storage.set(&tile_pos, Some(tile_entity));
+            }
+
+            commands
+                .entity(layer_entity)
+                .insert_bundle(TilemapBundle {
+                    grid_size,
+                    size: map_size,
+                    storage: tile_storage,
+                    texture: TilemapTexture(texture.clone()),
+                    tile_size,
+                    spacing: TilemapSpacing { x: ARENA_TILE_SPACING, y: ARENA_TILE_SPACING },
+                    transform: Transform::from_xyz(settings.origin.x, settings.origin.y, z as f32),
+                    mesh_type: TilemapMeshType::Square,
+                    ..Default::default()
+                })
+                .insert(RoundEntity);
+        }
+    }
+}
+
+/// Working state of the generator
+struct ArenaGrid {
+    width: u32,
+    height: u32,
+    road: Vec<bool>,
+    ruins: Vec<bool>,
+    solid: Vec<bool>,
+}
+
+impl ArenaGrid {
+    fn new(width: u32, height: u32) -> Self {
+        let len = (width * height) as usize;
+        Self {
+            width,
+            height,
+            road: vec![false; len],
+            ruins: vec![false; len],
+            solid: vec![false; len],
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    /// The tile opposite `tile` through the center of the map
+    fn mirrored(&self, tile: UVec2) -> UVec2 {
+        UVec2::new(self.width - 1 - tile.x, self.height - 1 - tile.y)
+    }
+
+    fn set_obstacle(&mut self, x: u32, y: u32) {
+        let i = self.index(x, y);
+        // Roads are never blocked, which keeps the road network connected
+        if !self.road[i] {
+            self.solid[i] = true;
+        }
+    }
+
+    /// Full length roads in mirrored pairs, every vertical road crosses every horizontal one
+    fn place_roads(&mut self, rng: &RandomNumbers) {
+        for _ in 0..ROAD_PAIRS {
+            let x = rng.range(SPAWN_MARGIN, self.width / 2 - ROAD_WIDTH);
+            let y = rng.range(SPAWN_MARGIN, self.height / 2 - ROAD_WIDTH);
+
+            for offset in 0..ROAD_WIDTH {
+                for row in 0..self.height {
+                    let left = self.index(x + offset, row);
+                    let right = self.index(self.width - 1 - x - offset, row);
+                    self.road[left] = true;
+                    self.road[right] = true;
+                }
+                for column in 0..self.width {
+                    let bottom = self.index(column, y + offset);
+                    let top = self.index(column, self.height - 1 - y - offset);
+                    self.road[bottom] = true;
+                    self.road[top] = true;
+                }
+            }
+        }
+    }
+
+    /// Patches of rubble floor with broken walls around them
+    fn place_ruins(&mut self, rng: &RandomNumbers) {
+        for _ in 0..RUIN_COUNT {
+            let (w, h) = (rng.range(4, 10), rng.range(4, 10));
+            let x = rng.range(1, self.width - w - 1);
+            let y = rng.range(1, self.height / 2 - h);
+
+            for ry in y..y + h {
+                for rx in x..x + w {
+                    let i = self.index(rx, ry);
+                    self.ruins[i] = true;
+
+                    let outline = rx == x || ry == y || rx == x + w - 1 || ry == y + h - 1;
+                    if outline && rng.random_bool(RUIN_WALL_CHANCE) {
+                        self.set_obstacle(rx, ry);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Small clusters of obstacles scattered around the map
+    fn place_rubble(&mut self, rng: &RandomNumbers) {
+        for _ in 0..RUBBLE_COUNT {
+            let (w, h) = (rng.range(1, 4), rng.range(1, 4));
+            let x = rng.range(1, self.width - w - 1);
+            let y = rng.range(1, self.height / 2);
+
+            for ry in y..y + h {
+                for rx in x..x + w {
+                    self.set_obstacle(rx, ry);
+                }
+            }
+        }
+    }
+
+    fn clear_around(&mut self, center: UVec2, radius: u32) {
+        let (x_begin, y_begin) = (center.x.saturating_sub(radius), center.y.saturating_sub(radius));
+        let x_end = u32::min(center.x + radius, self.width - 1);
+        let y_end = u32::min(center.y + radius, self.height - 1);
+
+        for y in y_begin..=y_end {
+            for x in x_begin..=x_end {
+                let i = self.index(x, y);
+                self.solid[i] = false;
+            }
+        }
+    }
+
+    /// Clear a straight line from `from` towards the center until it meets a road
+    fn carve_to_road(&mut self, from: UVec2) {
+        let step: i32 = if from.x < self.width / 2 { 1 } else { -1 };
+        let mut x = from.x as i32;
+
+        while x >= 0 && x < self.width as i32 {
+            let i = self.index(x as u32, from.y);
+            if self.road[i] {
+                break;
+            }
+            self.solid[i] = false;
+            x += step;
+        }
+    }
+
+    /// Copy the bottom half onto the top half, rotated 180 degrees around the center
+    fn mirror(&mut self) {
+        let len = self.solid.len();
+        for i in 0..len / 2 {
+            let j = len - 1 - i;
+            self.road[j] = self.road[i];
+            self.ruins[j] = self.ruins[i];
+            self.solid[j] = self.solid[i];
+        }
+    }
+
+    /// Flood fill from `start` and turn every walkable tile it can't reach into an obstacle,
+    /// so nothing can spawn in a sealed off pocket
+    fn fill_unreachable(&mut self, start: UVec2) {
+        let mut reached = vec![false; self.solid.len()];
+        let mut queue = VecDeque::new();
+
+        let start = self.index(start.x, start.y);
+        reached[start] = true;
+        queue.push_back(start);
+
+        while let Some(i) = queue.pop_front() {
+            let (x, y) = (i as u32 % self.width, i as u32 / self.width);
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < self.width).then(|| i + 1),
+                (y > 0).then(|| i - self.width as usize),
+                (y + 1 < self.height).then(|| i + self.width as usize),
+            ];
+
+            for n in neighbours.into_iter().flatten() {
+                if !reached[n] && !self.solid[n] {
+                    reached[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        for (solid, reached) in self.solid.iter_mut().zip(reached) {
+            *solid |= !reached;
+        }
+    }
+}
@@ -1,51 +1,164 @@
-use crate::{impl_new, prelude::*};
-use bevy::prelude::*;
+use crate::prelude::*;
+use bevy::{math::Vec3Swizzles, prelude::*};
 
 mod cache_grid;
+mod catalogue;
+mod collision;
+mod destructible;
+mod generator;
+mod objects;
+mod spawns;
+mod terrain;
 mod tiled;
 
 pub use self::tiled::*;
 pub use cache_grid::*;
+pub use catalogue::*;
+pub use collision::*;
+pub use destructible::*;
+pub use generator::*;
+pub use objects::*;
+pub use spawns::*;
+pub use terrain::*;
 
-const TILE_MAP_WIDTH: u32 = 128;
-const TILE_MAP_HEIGHT: u32 = 128;
+/// Map grid size the gameplay (creature sizes, speeds, ...) is tuned for
+pub const MAP_TILE_SIZE: u32 = 16;
 
+/// Gameplay bounds of the current map, derived from the loaded Tiled map or generated arena
 #[derive(Debug)]
 pub struct MapSettings {
     pub width: f32,
     pub height: f32,
+    pub tile_size: Vec2,
+    /// World position of the bottom left corner of the map
+    pub origin: Vec2,
 }
 
-impl_new!(MapSettings, width: f32, height: f32);
-
 impl MapSettings {
+    pub fn from_tiled(map: &::tiled::Map) -> Self {
+        let tile_size = Vec2::new(map.tile_width as f32, map.tile_height as f32);
+        Self::from_grid(map.width, map.height, tile_size)
+    }
+
+    /// The tilemap is centered on the world origin, see `process_loaded_maps`
+    pub fn from_grid(columns: u32, rows: u32, tile_size: Vec2) -> Self {
+        let width = columns as f32 * tile_size.x;
+        let height = rows as f32 * tile_size.y;
+
+        Self { width, height, tile_size, origin: -Vec2::new(width, height) / 2.0 }
+    }
+
     pub fn size(&self) -> f32 {
         self.width * self.height
     }
+
+    pub fn min(&self) -> Vec2 {
+        self.origin
+    }
+
+    pub fn max(&self) -> Vec2 {
+        self.origin + Vec2::new(self.width, self.height)
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.origin + Vec2::new(self.width, self.height) / 2.0
+    }
+
+    pub fn clamp(&self, pos: Vec2) -> Vec2 {
+        pos.clamp(self.min(), self.max())
+    }
 }
 
-fn startup(mut commands: Commands, map_assets: Res<MapAssets>) {
-    let tilemap_size = TilemapSize { x: TILE_MAP_WIDTH, y: TILE_MAP_HEIGHT };
+fn startup(mut commands: Commands, asset_server: Res<AssetServer>, selected_map: Res<SelectedMap>) {
+    match &selected_map.entry.source {
+        MapSource::Tiled(_) => {
+            // Spawn Tilemap, the rest happens in `finish_world_gen` once it has loaded
+            commands
+                .spawn()
+                .insert_bundle(TiledMapBundle {
+                    tiled_map: selected_map.map.clone().unwrap(),
+                    ..Default::default()
+                })
+                .insert(RoundEntity);
+        }
+        MapSource::Generated(arena_settings) => {
+            info!("Generating arena with seed {:#x}", selected_map.seed);
+
+            let arena = Arena::generate(arena_settings, selected_map.seed);
+            let settings = arena.map_settings();
+            arena.spawn(&mut commands, asset_server.load(ARENA_TILESET), &settings);
 
-    let tile_size = TilemapTileSize { x: 16.0, y: 16.0 };
+            commands.insert_resource(arena.collision_map(&settings));
+            commands.insert_resource(arena.spawn_points(&settings));
+            commands.insert_resource(arena.terrain_map(&settings));
+            commands.insert_resource(MapObjects::default());
+            commands.insert_resource(settings);
+
+            commands.insert_resource(NextState(AppState::InGame))
+        }
+    }
+}
 
-    // Spawn Tilemap
-    commands
-        .spawn()
-        .insert_bundle(TiledMapBundle { tiled_map: map_assets.map.clone(), ..Default::default() })
-        .insert(RoundEntity);
+/// Waits for the map asset before deriving the round's map resources from it
+fn finish_world_gen(
+    mut commands: Commands,
+    selected_map: Res<SelectedMap>,
+    maps: Res<Assets<TiledMap>>,
+) {
+    let tiled_map = match selected_map.map.as_ref().and_then(|map| maps.get(map)) {
+        Some(tiled_map) => tiled_map,
+        None => return,
+    };
 
-    commands.insert_resource(MapSettings::new(
-        tilemap_size.x as f32 * tile_size.x,
-        tilemap_size.y as f32 * tile_size.y,
-    ));
+    let settings = MapSettings::from_tiled(&tiled_map.map);
+    commands.insert_resource(CollisionMap::from_tiled(&tiled_map.map, &settings));
+    commands.insert_resource(SpawnPoints::from_tiled(&tiled_map.map, &settings));
+    commands.insert_resource(TerrainMap::from_tiled(&tiled_map.map, &settings));
+    commands.insert_resource(MapObjects::from_tiled(&tiled_map.map, &settings));
+    commands.insert_resource(settings);
 
     commands.insert_resource(NextState(AppState::InGame))
 }
 
+/// Warns when a spawned tilemap layer doesn't cover the gameplay bounds
+fn verify_map_bounds(
+    settings: Res<MapSettings>,
+    layers: Query<(&TilemapSize, &TilemapGridSize, &Transform), Added<TileStorage>>,
+) {
+    for (size, grid_size, transform) in layers.iter() {
+        let layer_min = transform.translation.xy();
+        let layer_size = Vec2::new(size.x as f32 * grid_size.x, size.y as f32 * grid_size.y);
+
+        if layer_min.distance(settings.min()) > 0.5
+            || layer_size.distance(Vec2::new(settings.width, settings.height)) > 0.5
+        {
+            warn!(
+                "Tilemap layer at {:?} sized {:?} does not line up with map bounds {:?} - {:?}",
+                layer_min,
+                layer_size,
+                settings.min(),
+                settings.max()
+            );
+        }
+    }
+}
+
 pub struct MapPlugin;
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_enter_system(AppState::WorldGen, startup);
+        app.add_event::<StructureDamageEvent>();
+
+        app.add_asset::<MapCatalogue>()
+            .add_asset_loader(MapCatalogueLoader)
+            .add_enter_system(AppState::WorldGen, startup)
+            .add_system(finish_world_gen.run_in_state(AppState::WorldGen))
+            .add_system(verify_map_bounds.run_in_state(AppState::InGame))
+            .add_system(
+                damage_structures
+                    .run_in_state(AppState::InGame)
+                    .run_in_state(InGameState::Playing)
+                    .run_on_event::<StructureDamageEvent>()
+                    .after(SystemLabels::BulletMove),
+            );
     }
 }
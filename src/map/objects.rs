@@ -0,0 +1,113 @@
+use crate::map::*;
+
+/// Object types the round turns into interactive entities, matched like spawn objects
+pub const TELEPORTER: &str = "teleporter";
+pub const DOOR: &str = "door";
+pub const PRESSURE_PLATE: &str = "pressure_plate";
+pub const HAZARD: &str = "hazard";
+pub const EXPLOSIVE_BARREL: &str = "explosive_barrel";
+
+pub const DEFAULT_TELEPORT_COOLDOWN: f32 = 2.;
+pub const DEFAULT_HAZARD_INTERVAL: f32 = 1.;
+pub const DEFAULT_BARREL_RADIUS: f32 = 80.;
+pub const DEFAULT_BARREL_DAMAGE: u32 = 2;
+pub const DEFAULT_BARREL_KNOCKBACK: f32 = 60.;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapObjectKind {
+    /// Sends whatever steps on it to the other teleporter with the same `pair`
+    Teleporter { pair: String, cooldown: f32 },
+    /// Solid while closed
+    Door { id: String, open: bool },
+    /// Toggles every door with the id in `door`, or holds them open while pressed with `hold`
+    PressurePlate { door: String, hold: bool },
+    /// Spikes, fire, ... hurts anything inside every `damage_interval` seconds
+    Hazard { damage_interval: f32 },
+    /// Blows up when shot
+    ExplosiveBarrel { radius: f32, damage: u32, knockback: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapObject {
+    pub name: String,
+    pub rect: SpawnRect,
+    pub kind: MapObjectKind,
+}
+
+/// Interactive objects read from the object layers of the current map
+#[derive(Debug, Default)]
+pub struct MapObjects(pub Vec<MapObject>);
+
+impl MapObjects {
+    pub fn from_tiled(map: &::tiled::Map, settings: &MapSettings) -> Self {
+        let mut objects = Vec::new();
+
+        for group in map.object_groups.iter() {
+            for object in group.objects.iter() {
+                let kind = if object.obj_type.is_empty() { &group.name } else { &object.obj_type };
+                let props = &object.properties;
+
+                let kind = match kind.as_str() {
+                    TELEPORTER => MapObjectKind::Teleporter {
+                        pair: string_property(props, "pair").unwrap_or_default(),
+                        cooldown: float_property(props, "cooldown")
+                            .unwrap_or(DEFAULT_TELEPORT_COOLDOWN),
+                    },
+                    DOOR => MapObjectKind::Door {
+                        id: string_property(props, "id").unwrap_or_else(|| object.name.clone()),
+                        open: bool_property(props, "open").unwrap_or(false),
+                    },
+                    PRESSURE_PLATE => MapObjectKind::PressurePlate {
+                        door: string_property(props, "door").unwrap_or_default(),
+                        hold: bool_property(props, "hold").unwrap_or(false),
+                    },
+                    HAZARD => MapObjectKind::Hazard {
+                        damage_interval: float_property(props, "damage_interval")
+                            .unwrap_or(DEFAULT_HAZARD_INTERVAL),
+                    },
+                    EXPLOSIVE_BARREL => MapObjectKind::ExplosiveBarrel {
+                        radius: float_property(props, "radius").unwrap_or(DEFAULT_BARREL_RADIUS),
+                        damage: int_property(props, "damage")
+                            .map_or(DEFAULT_BARREL_DAMAGE, |d| d.max(0) as u32),
+                        knockback: float_property(props, "knockback")
+                            .unwrap_or(DEFAULT_BARREL_KNOCKBACK),
+                    },
+                    _ => continue,
+                };
+
+                // Point objects take up a single tile
+                let mut rect = object_rect(object, settings);
+                if rect.min == rect.max {
+                    rect.min -= settings.tile_size / 2.0;
+                    rect.max += settings.tile_size / 2.0;
+                }
+
+                objects.push(MapObject { name: object.name.clone(), rect, kind });
+            }
+        }
+
+        Self(objects)
+    }
+}
+
+fn string_property(properties: &::tiled::Properties, name: &str) -> Option<String> {
+    match properties.get(name) {
+        Some(::tiled::PropertyValue::StringValue(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn float_property(properties: &::tiled::Properties, name: &str) -> Option<f32> {
+    match properties.get(name) {
+        Some(::tiled::PropertyValue::FloatValue(value)) => Some(*value),
+        Some(::tiled::PropertyValue::IntValue(value)) => Some(*value as f32),
+        _ => None,
+    }
+}
+
+fn bool_property(properties: &::tiled::Properties, name: &str) -> Option<bool> {
+    match properties.get(name) {
+        Some(::tiled::PropertyValue::BoolValue(value)) => Some(*value),
+        _ => None,
+    }
+}
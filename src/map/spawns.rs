@@ -0,0 +1,187 @@
+use crate::map::*;
+
+/// Objects are picked up either by their type or by the name of their object layer
+pub const PLAYER_SPAWN: &str = "player_spawn";
+pub const ZOMBIE_ZONE: &str = "zombie_zone";
+pub const NO_SPAWN: &str = "no_spawn";
+pub const ZONE_WEIGHT_PROPERTY: &str = "weight";
+
+/// Attempts at finding a free random point before giving up on the constraints
+const MAX_SPAWN_ATTEMPTS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnRect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl SpawnRect {
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Does a circle at `point` overlap this rect?
+    pub fn overlaps(&self, point: Vec2, radius: f32) -> bool {
+        point.clamp(self.min, self.max).distance_squared(point) <= radius * radius
+    }
+
+    pub fn random_point(&self, rng: &RandomNumbers) -> Vec2 {
+        if self.min.x >= self.max.x || self.min.y >= self.max.y {
+            return self.min;
+        }
+        Vec2::new(rng.range(self.min.x, self.max.x), rng.range(self.min.y, self.max.y))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnZone {
+    pub rect: SpawnRect,
+    pub weight: f32,
+}
+
+/// Spawn locations read from the object layers of the current map
+#[derive(Debug, Default)]
+pub struct SpawnPoints {
+    pub players: Vec<(String, Vec2)>,
+    pub zombie_zones: Vec<SpawnZone>,
+    pub no_spawn: Vec<SpawnRect>,
+}
+
+impl SpawnPoints {
+    pub fn from_tiled(map: &::tiled::Map, settings: &MapSettings) -> Self {
+        let mut spawns = SpawnPoints::default();
+
+        for group in map.object_groups.iter() {
+            for object in group.objects.iter() {
+                let rect = object_rect(object, settings);
+                let kind = if object.obj_type.is_empty() { &group.name } else { &object.obj_type };
+
+                match kind.as_str() {
+                    PLAYER_SPAWN => spawns.players.push((object.name.clone(), rect.center())),
+                    ZOMBIE_ZONE => {
+                        let weight = match object.properties.get(ZONE_WEIGHT_PROPERTY) {
+                            Some(::tiled::PropertyValue::FloatValue(w)) => *w,
+                            Some(::tiled::PropertyValue::IntValue(w)) => *w as f32,
+                            _ => 1.0,
+                        };
+                        spawns.zombie_zones.push(SpawnZone { rect, weight: weight.max(0.0) });
+                    }
+                    NO_SPAWN => spawns.no_spawn.push(rect),
+                    _ => {}
+                }
+            }
+        }
+
+        // Keep spawn order stable regardless of how the map was edited
+        spawns.players.sort_by(|a, b| a.0.cmp(&b.0));
+        spawns
+    }
+
+    pub fn is_blocked(&self, point: Vec2, radius: f32, collision_map: &CollisionMap) -> bool {
+        self.no_spawn.iter().any(|r| r.contains(point)) || collision_map.collides(point, radius)
+    }
+
+    /// The player spawn point furthest away from everyone in `others`
+    pub fn player_spawn(
+        &self,
+        others: &[Vec2],
+        radius: f32,
+        rng: &RandomNumbers,
+        settings: &MapSettings,
+        collision_map: &CollisionMap,
+    ) -> Vec2 {
+        if self.players.is_empty() {
+            return self.random_free_point(radius, rng, settings, collision_map);
+        }
+
+        if others.is_empty() {
+            let (_, point) = rng.random_slice_entry(&self.players).unwrap();
+            return *point;
+        }
+
+        let furthest =
+            |p: &Vec2| others.iter().map(|o| o.distance_squared(*p)).fold(f32::INFINITY, f32::min);
+        self.players
+            .iter()
+            .map(|(_, p)| *p)
+            .max_by(|a, b| furthest(a).total_cmp(&furthest(b)))
+            .unwrap()
+    }
+
+    /// A point inside one of the weighted zombie zones
+    pub fn zombie_spawn(
+        &self,
+        radius: f32,
+        rng: &RandomNumbers,
+        settings: &MapSettings,
+        collision_map: &CollisionMap,
+    ) -> Vec2 {
+        let total_weight: f32 = self.zombie_zones.iter().map(|z| z.weight).sum();
+        if total_weight <= 0.0 {
+            return self.random_free_point(radius, rng, settings, collision_map);
+        }
+
+        let mut point = Vec2::ZERO;
+        for _ in 0..MAX_SPAWN_ATTEMPTS {
+            let mut roll = rng.range(0.0, total_weight);
+            let zone = self
+                .zombie_zones
+                .iter()
+                .find(|z| {
+                    roll -= z.weight;
+                    roll < 0.0
+                })
+                .unwrap_or_else(|| self.zombie_zones.last().unwrap());
+
+            point = zone.rect.random_point(rng);
+            if !self.is_blocked(point, radius, collision_map) {
+                break;
+            }
+        }
+
+        point
+    }
+
+    /// Uniform point on the map, avoiding no-spawn regions and walls where possible
+    pub fn random_free_point(
+        &self,
+        radius: f32,
+        rng: &RandomNumbers,
+        settings: &MapSettings,
+        collision_map: &CollisionMap,
+    ) -> Vec2 {
+        let mut point = Vec2::ZERO;
+        for _ in 0..MAX_SPAWN_ATTEMPTS {
+            let (x, y) = random_map_point(settings, rng);
+            point = Vec2::new(x, y);
+            if !self.is_blocked(point, radius, collision_map) {
+                break;
+            }
+        }
+
+        point
+    }
+}
+
+/// Tiled objects are positioned from the top left of the map with y pointing down
+pub fn object_rect(object: &::tiled::Object, settings: &MapSettings) -> SpawnRect {
+    let (width, height) = match object.shape {
+        ::tiled::ObjectShape::Rect { width, height }
+        | ::tiled::ObjectShape::Ellipse { width, height } => (width, height),
+        _ => (0.0, 0.0),
+    };
+
+    let top_left = Vec2::new(settings.min().x + object.x, settings.max().y - object.y);
+    SpawnRect {
+        min: Vec2::new(top_left.x, top_left.y - height),
+        max: Vec2::new(top_left.x + width, top_left.y),
+    }
+}
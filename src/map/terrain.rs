@@ -0,0 +1,135 @@
+use crate::map::*;
+use bevy::utils::HashMap;
+
+/// Float property on layers or tileset tiles, scales the speed of everything on them
+pub const SPEED_MULTIPLIER_PROPERTY: &str = "speed_multiplier";
+/// Bool property on layers or tileset tiles, zombies are slowed down further on them
+pub const SLOWS_ZOMBIES_PROPERTY: &str = "slows_zombies";
+pub const SLOWED_ZOMBIE_MULTIPLIER: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainModifier {
+    pub speed_multiplier: f32,
+    pub slows_zombies: bool,
+}
+
+impl Default for TerrainModifier {
+    fn default() -> Self {
+        Self { speed_multiplier: 1.0, slows_zombies: false }
+    }
+}
+
+impl TerrainModifier {
+    /// `None` when the properties don't change anything
+    pub fn from_properties(properties: &::tiled::Properties) -> Option<Self> {
+        let speed_multiplier = match properties.get(SPEED_MULTIPLIER_PROPERTY) {
+            Some(::tiled::PropertyValue::FloatValue(multiplier)) => Some(*multiplier),
+            Some(::tiled::PropertyValue::IntValue(multiplier)) => Some(*multiplier as f32),
+            _ => None,
+        };
+        let slows_zombies = match properties.get(SLOWS_ZOMBIES_PROPERTY) {
+            Some(::tiled::PropertyValue::BoolValue(slows)) => Some(*slows),
+            _ => None,
+        };
+
+        if speed_multiplier.is_none() && slows_zombies.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            speed_multiplier: speed_multiplier.unwrap_or(1.0).max(0.0),
+            slows_zombies: slows_zombies.unwrap_or(false),
+        })
+    }
+
+    pub fn player_speed(&self) -> f32 {
+        self.speed_multiplier
+    }
+
+    pub fn zombie_speed(&self) -> f32 {
+        if self.slows_zombies {
+            self.speed_multiplier * SLOWED_ZOMBIE_MULTIPLIER
+        } else {
+            self.speed_multiplier
+        }
+    }
+}
+
+/// Movement modifiers of the current map, laid out like `CollisionMap`.
+/// The topmost visible layer with a modifier on a tile decides it, but tile properties win over
+/// layer ones wherever they are in the stack.
+#[derive(Debug, Default)]
+pub struct TerrainMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: Vec2,
+    pub origin: Vec2,
+    pub modifiers: Vec<TerrainModifier>,
+}
+
+impl TerrainMap {
+    pub fn from_tiled(map: &::tiled::Map, settings: &MapSettings) -> Self {
+        let (width, height) = (map.width, map.height);
+        let mut modifiers = vec![TerrainModifier::default(); (width * height) as usize];
+
+        // Cells whose modifier comes from a tile property, layer properties can't override those
+        let mut from_tile = vec![false; modifiers.len()];
+
+        let mut tile_modifiers = HashMap::default();
+        for tileset in map.tilesets.iter() {
+            for tile in tileset.tiles.iter() {
+                if let Some(modifier) = TerrainModifier::from_properties(&tile.properties) {
+                    tile_modifiers.insert(tileset.first_gid + tile.id, modifier);
+                }
+            }
+        }
+
+        for layer in map.layers.iter() {
+            if !layer.visible {
+                continue;
+            }
+            let layer_modifier = TerrainModifier::from_properties(&layer.properties);
+
+            let tiles = match &layer.tiles {
+                ::tiled::LayerData::Finite(tiles) => tiles,
+                _ => continue,
+            };
+
+            for (row, row_tiles) in tiles.iter().enumerate() {
+                for (x, tile) in row_tiles.iter().enumerate() {
+                    if tile.gid == 0 {
+                        continue;
+                    }
+
+                    // Tiled rows go top-down, tilemap rows go bottom-up
+                    let i = (height as usize - 1 - row) * width as usize + x;
+                    if let Some(modifier) = tile_modifiers.get(&tile.gid) {
+                        modifiers[i] = *modifier;
+                        from_tile[i] = true;
+                    } else if let Some(modifier) = layer_modifier.filter(|_| !from_tile[i]) {
+                        modifiers[i] = modifier;
+                    }
+                }
+            }
+        }
+
+        Self { width, height, tile_size: settings.tile_size, origin: settings.origin, modifiers }
+    }
+
+    pub fn modifier(&self, pos: Vec2) -> TerrainModifier {
+        let local = (pos - self.origin) / self.tile_size;
+        let (x, y) = (local.x.floor() as i32, local.y.floor() as i32);
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return TerrainModifier::default();
+        }
+        self.modifiers[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn player_speed(&self, pos: Vec2) -> f32 {
+        self.modifier(pos).player_speed()
+    }
+
+    pub fn zombie_speed(&self, pos: Vec2) -> f32 {
+        self.modifier(pos).zombie_speed()
+    }
+}
@@ -1,23 +1,33 @@
 use crate::prelude::*;
-use std::io::BufReader;
+use std::{io::BufReader, ops::Range, path::Path};
 
+use anyhow::{anyhow, bail};
 use bevy::{
-    asset::{AssetLoader, AssetPath, LoadedAsset},
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
     prelude::{
-        AddAsset, Added, AssetEvent, Assets, Bundle, Commands, Component, DespawnRecursiveExt,
-        Entity, EventReader, GlobalTransform, Handle, Image, Plugin, Query, Res, Transform,
+        AddAsset, Added, AssetEvent, Assets, Bundle, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, EventReader, GlobalTransform, Handle, Image, Plugin, Query,
+        Res, Time, Transform,
     },
     reflect::TypeUuid,
+    render::{
+        render_resource::{Extent3d, TextureDimension},
+        texture::{CompressedImageFormats, ImageType},
+    },
     utils::HashMap,
 };
 use bevy_ecs_tilemap::prelude::*;
+use xml::{reader::XmlEvent, EmitterConfig};
 
 #[derive(Default)]
 pub struct TiledMapPlugin;
 
 impl Plugin for TiledMapPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_asset::<TiledMap>().add_asset_loader(TiledLoader).add_system(process_loaded_maps);
+        app.add_asset::<TiledMap>()
+            .add_asset_loader(TiledLoader)
+            .add_system(process_loaded_maps)
+            .add_system(animate_tiles);
     }
 }
 
@@ -25,13 +35,25 @@ impl Plugin for TiledMapPlugin {
 #[uuid = "e51081d0-6168-4881-a1c6-4249b2000d7f"]
 pub struct TiledMap {
     pub map: tiled::Map,
-    pub tilesets: HashMap<u32, Handle<Image>>,
+    pub textures: Vec<TiledTexture>,
+}
+
+/// One texture tiles are drawn from: the atlas of a tileset, or a single image of an
+/// image collection tileset
+pub struct TiledTexture {
+    pub texture: Handle<Image>,
+    pub tile_size: TilemapTileSize,
+    pub spacing: TilemapSpacing,
+    /// Global tile ids found in this texture, the first one is at index 0
+    pub gids: Range<u32>,
+    /// Whether this is a whole tileset, so tile ids can be used as texture indices
+    pub atlas: bool,
 }
 
-// Stores a list of tiled layers.
+// Stores a list of tiled layers. A layer is split into one tilemap per texture it uses.
 #[derive(Component, Default)]
 pub struct TiledLayersStorage {
-    pub storage: HashMap<u32, Entity>,
+    pub storage: HashMap<u32, Vec<Entity>>,
 }
 
 #[derive(Default, Bundle)]
@@ -42,6 +64,12 @@ pub struct TiledMapBundle {
     pub global_transform: GlobalTransform,
 }
 
+/// Frames of a Tiled tile animation, as texture indices and durations in seconds
+#[derive(Component, Debug, Clone)]
+pub struct TiledAnimation {
+    pub frames: Vec<(u32, f32)>,
+}
+
 pub struct TiledLoader;
 
 impl AssetLoader for TiledLoader {
@@ -51,25 +79,103 @@ impl AssetLoader for TiledLoader {
         load_context: &'a mut bevy::asset::LoadContext,
     ) -> bevy::asset::BoxedFuture<'a, anyhow::Result<(), anyhow::Error>> {
         Box::pin(async move {
-            let root_dir = load_context.path().parent().unwrap();
-            let map = tiled::parse(BufReader::new(bytes))?;
+            let map_path = load_context.path().to_path_buf();
+            let root_dir = map_path
+                .parent()
+                .ok_or_else(|| anyhow!("{:?} has no parent folder", map_path))?
+                .to_path_buf();
+
+            let (mut map, external_tilesets) = parse_tiled_map(bytes)
+                .map_err(|e| anyhow!("Failed to parse {:?}: {}", map_path, e))?;
+
+            // Image paths in a .tsx file are relative to that file
+            let mut image_dirs = HashMap::default();
+            for (first_gid, tsx) in external_tilesets {
+                let tsx_path = root_dir.join(tsx);
+                let tsx_bytes = load_context.read_asset_bytes(&tsx_path).await?;
+                let tileset = parse_external_tileset(&tsx_bytes, first_gid)
+                    .map_err(|e| anyhow!("Failed to parse tileset {:?}: {}", tsx_path, e))?;
+
+                image_dirs.insert(first_gid, tsx_path.parent().unwrap_or(&root_dir).to_path_buf());
+                map.tilesets.push(tileset);
+            }
+            map.tilesets.sort_by_key(|tileset| tileset.first_gid);
 
             let mut dependencies = Vec::new();
-            let mut handles = HashMap::default();
+            let mut textures = Vec::new();
 
             for tileset in &map.tilesets {
-                let tile_path = root_dir.join(tileset.images.first().unwrap().source.as_str());
-                let asset_path = AssetPath::new(tile_path, None);
-                let texture: Handle<Image> = load_context.get_handle(asset_path.clone());
+                let image_dir = image_dirs.get(&tileset.first_gid).unwrap_or(&root_dir);
+                let spacing =
+                    TilemapSpacing { x: tileset.spacing as f32, y: tileset.spacing as f32 };
 
-                for i in tileset.first_gid..(tileset.first_gid + tileset.tilecount.unwrap_or(1)) {
-                    handles.insert(i, texture.clone());
-                }
+                match tileset.images.as_slice() {
+                    // Single atlas image
+                    [image] => {
+                        let tile_path = image_dir.join(image.source.as_str());
+                        let texture = if tileset.margin > 0 {
+                            // The tilemap renderer has no notion of margins, so cut them off
+                            load_without_margin(load_context, &tile_path, tileset).await?
+                        } else {
+                            let asset_path = AssetPath::new(tile_path, None);
+                            dependencies.push(asset_path.clone());
+                            load_context.get_handle(asset_path)
+                        };
 
-                dependencies.push(asset_path);
+                        let tilecount = tileset.tilecount.unwrap_or_else(|| {
+                            let columns = (image.width as u32 - 2 * tileset.margin
+                                + tileset.spacing)
+                                / (tileset.tile_width + tileset.spacing);
+                            let rows = (image.height as u32 - 2 * tileset.margin + tileset.spacing)
+                                / (tileset.tile_height + tileset.spacing);
+                            columns * rows
+                        });
+
+                        textures.push(TiledTexture {
+                            texture,
+                            tile_size: TilemapTileSize {
+                                x: tileset.tile_width as f32,
+                                y: tileset.tile_height as f32,
+                            },
+                            spacing,
+                            gids: tileset.first_gid..tileset.first_gid + tilecount,
+                            atlas: true,
+                        });
+                    }
+                    // Image collection, every tile has its own image
+                    [] => {
+                        for tile in tileset.tiles.iter() {
+                            let image = match tile.images.first() {
+                                Some(image) => image,
+                                None => continue,
+                            };
+
+                            let asset_path =
+                                AssetPath::new(image_dir.join(image.source.as_str()), None);
+                            dependencies.push(asset_path.clone());
+
+                            let gid = tileset.first_gid + tile.id;
+                            textures.push(TiledTexture {
+                                texture: load_context.get_handle(asset_path),
+                                tile_size: TilemapTileSize {
+                                    x: image.width as f32,
+                                    y: image.height as f32,
+                                },
+                                spacing,
+                                gids: gid..gid + 1,
+                                atlas: false,
+                            });
+                        }
+                    }
+                    _ => bail!(
+                        "{:?}: tileset '{}' has more than one image, which Tiled doesn't support",
+                        map_path,
+                        tileset.name
+                    ),
+                }
             }
 
-            let loaded_asset = LoadedAsset::new(TiledMap { map, tilesets: handles });
+            let loaded_asset = LoadedAsset::new(TiledMap { map, textures });
             load_context.set_default_asset(loaded_asset.with_dependencies(dependencies));
             Ok(())
         })
@@ -81,6 +187,114 @@ impl AssetLoader for TiledLoader {
     }
 }
 
+/// Parses a .tmx file the way `TiledLoader` does. `tiled` can only read external tilesets straight
+/// from the file system, so they are cut out of the map and returned as (first gid, .tsx path)
+/// pairs, to be read by the caller and parsed with `parse_external_tileset`.
+pub fn parse_tiled_map(bytes: &[u8]) -> anyhow::Result<(tiled::Map, Vec<(u32, String)>)> {
+    let (source, external_tilesets) = split_external_tilesets(bytes)?;
+    let map = tiled::parse(BufReader::new(source.as_slice())).map_err(|e| anyhow!("{:?}", e))?;
+
+    if map.infinite {
+        bail!("infinite maps are not supported");
+    }
+
+    Ok((map, external_tilesets))
+}
+
+pub fn parse_external_tileset(bytes: &[u8], first_gid: u32) -> anyhow::Result<tiled::Tileset> {
+    tiled::parse_tileset(BufReader::new(bytes), first_gid).map_err(|e| anyhow!("{:?}", e))
+}
+
+/// Copies the map document without its `<tileset firstgid=".." source=".."/>` references,
+/// returning them as (first gid, .tsx path) pairs
+fn split_external_tilesets(bytes: &[u8]) -> anyhow::Result<(Vec<u8>, Vec<(u32, String)>)> {
+    let mut stripped = Vec::with_capacity(bytes.len());
+    let mut external = Vec::new();
+
+    let mut writer = EmitterConfig::new().perform_indent(false).create_writer(&mut stripped);
+    // Depth inside an external tileset reference, which is left out along with its children
+    let mut skipping = 0;
+
+    for event in xml::EventReader::new(bytes) {
+        let event = event?;
+        if skipping > 0 {
+            match event {
+                XmlEvent::StartElement { .. } => skipping += 1,
+                XmlEvent::EndElement { .. } => skipping -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        if let XmlEvent::StartElement { name, attributes, .. } = &event {
+            let attribute = |key: &str| {
+                attributes.iter().find(|a| a.name.local_name == key).map(|a| a.value.as_str())
+            };
+
+            if let (true, Some(tsx)) = (name.local_name == "tileset", attribute("source")) {
+                let first_gid = attribute("firstgid")
+                    .ok_or_else(|| anyhow!("external tileset {} has no firstgid", tsx))?
+                    .parse()?;
+                external.push((first_gid, tsx.to_string()));
+                skipping = 1;
+                continue;
+            }
+        }
+
+        if let Some(event) = event.as_writer_event() {
+            writer.write(event)?;
+        }
+    }
+    drop(writer);
+
+    Ok((stripped, external))
+}
+
+async fn load_without_margin<'a>(
+    load_context: &mut LoadContext<'a>,
+    path: &Path,
+    tileset: &tiled::Tileset,
+) -> anyhow::Result<Handle<Image>> {
+    let bytes = load_context.read_asset_bytes(path).await?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension(extension),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(|e| anyhow!("Failed to load tileset image {:?}: {:?}", path, e))?;
+
+    let margin = tileset.margin as usize;
+    let size = image.texture_descriptor.size;
+    let (width, height) = (size.width as usize, size.height as usize);
+    if width <= 2 * margin || height <= 2 * margin {
+        bail!("Tileset image {:?} is smaller than the margins of '{}'", path, tileset.name);
+    }
+
+    let pixel_size = image.texture_descriptor.format.describe().block_size as usize;
+    let row_size = (width - 2 * margin) * pixel_size;
+    let mut data = Vec::with_capacity(row_size * (height - 2 * margin));
+    for y in margin..height - margin {
+        let row_start = (y * width + margin) * pixel_size;
+        data.extend_from_slice(&image.data[row_start..row_start + row_size]);
+    }
+
+    let cropped = Image::new(
+        Extent3d {
+            width: (width - 2 * margin) as u32,
+            height: (height - 2 * margin) as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        image.texture_descriptor.format,
+    );
+
+    let label = format!("tileset{}", tileset.first_gid);
+    Ok(load_context.set_labeled_asset(&label, LoadedAsset::new(cropped)))
+}
+
 pub fn process_loaded_maps(
     mut commands: Commands,
     mut map_events: EventReader<AssetEvent<TiledMap>>,
@@ -124,49 +338,62 @@ pub fn process_loaded_maps(
                 continue;
             }
             if let Some(tiled_map) = maps.get(map_handle) {
+                // Layers are placed on the map grid so they line up with the gameplay bounds,
+                // even when a tileset's tiles are larger than the grid
+                let map_settings = MapSettings::from_tiled(&tiled_map.map);
+
                 // TODO: Create a RemoveMap component..
-                for layer_entity in layer_storage.storage.values() {
+                for layer_entity in layer_storage.storage.values().flatten() {
                     if let Ok((_, layer_tile_storage)) = tile_storage_query.get(*layer_entity) {
                         for tile in layer_tile_storage.iter().flatten() {
                             commands.entity(*tile).despawn_recursive()
                         }
                     }
+                    commands.entity(*layer_entity).despawn_recursive();
                 }
+                layer_storage.storage.clear();
 
-                for tileset in tiled_map.map.tilesets.iter() {
-                    // Once materials have been created/added we need to then create the layers.
-                    for layer in tiled_map.map.layers.iter() {
-                        let tile_size = TilemapTileSize {
-                            x: tileset.tile_width as f32,
-                            y: tileset.tile_height as f32,
-                        };
-                        let tile_spacing =
-                            TilemapSpacing { x: tileset.spacing as f32, y: tileset.spacing as f32 };
+                let animations = tile_animations(&tiled_map.map);
+                let destructibles = destructible_tiles(&tiled_map.map);
 
-                        let offset_x = layer.offset_x;
-                        let offset_y = layer.offset_y;
+                for layer in tiled_map.map.layers.iter() {
+                    if !layer.visible {
+                        continue;
+                    }
 
-                        let map_size =
-                            TilemapSize { x: tiled_map.map.width, y: tiled_map.map.height };
+                    // Infinite maps are rejected by the loader
+                    let tiles = match &layer.tiles {
+                        tiled::LayerData::Finite(tiles) => tiles,
+                        _ => continue,
+                    };
 
-                        let grid_size = TilemapGridSize {
-                            x: tiled_map.map.tile_width as f32,
-                            y: tiled_map.map.tile_height as f32,
-                        };
+                    let offset_x = layer.offset_x;
+                    let offset_y = layer.offset_y;
+                    let color = TileColor(Color::rgba(1., 1., 1., layer.opacity));
 
-                        let mesh_type = match tiled_map.map.orientation {
-                            tiled::Orientation::Hexagonal => TilemapMeshType::Hexagon(HexType::Row),
-                            tiled::Orientation::Isometric => {
-                                TilemapMeshType::Isometric(IsoType::Diamond)
-                            }
-                            tiled::Orientation::Staggered => {
-                                TilemapMeshType::Isometric(IsoType::Staggered)
-                            }
-                            tiled::Orientation::Orthogonal => TilemapMeshType::Square,
-                        };
+                    let map_size = TilemapSize { x: tiled_map.map.width, y: tiled_map.map.height };
+
+                    let grid_size = TilemapGridSize {
+                        x: tiled_map.map.tile_width as f32,
+                        y: tiled_map.map.tile_height as f32,
+                    };
+
+                    let mesh_type = match tiled_map.map.orientation {
+                        tiled::Orientation::Hexagonal => TilemapMeshType::Hexagon(HexType::Row),
+                        tiled::Orientation::Isometric => {
+                            TilemapMeshType::Isometric(IsoType::Diamond)
+                        }
+                        tiled::Orientation::Staggered => {
+                            TilemapMeshType::Isometric(IsoType::Staggered)
+                        }
+                        tiled::Orientation::Orthogonal => TilemapMeshType::Square,
+                    };
 
+                    // Once textures have been loaded we need to then create the layers.
+                    for texture in tiled_map.textures.iter() {
                         let mut tile_storage = TileStorage::empty(map_size);
                         let layer_entity = commands.spawn().id();
+                        let mut empty = true;
 
                         for x in 0..map_size.x {
                             for y in 0..map_size.y {
@@ -175,72 +402,120 @@ pub fn process_loaded_maps(
                                     mapped_y = (tiled_map.map.height - 1) as u32 - y;
                                 }
 
-                                let mapped_x = x as usize;
-                                let mapped_y = mapped_y as usize;
-
-                                let map_tile = match &layer.tiles {
-                                    tiled::LayerData::Finite(tiles) => &tiles[mapped_y][mapped_x],
-                                    _ => panic!("Infinite maps not supported"),
-                                };
-
-                                if map_tile.gid < tileset.first_gid
-                                    || map_tile.gid
-                                        >= tileset.first_gid + tileset.tilecount.unwrap()
-                                {
+                                let map_tile = &tiles[mapped_y as usize][x as usize];
+                                if !texture.gids.contains(&map_tile.gid) {
                                     continue;
                                 }
 
-                                let tile_id = map_tile.gid - tileset.first_gid;
-
                                 let tile_pos = TilePos { x, y };
-                                let tile_entity = commands
-                                    .spawn()
+                                let mut tile_entity = commands.spawn();
+                                tile_entity
                                     .insert_bundle(TileBundle {
                                         position: tile_pos,
                                         tilemap_id: TilemapId(layer_entity),
-                                        texture: TileTexture(tile_id),
+                                        texture: TileTexture(map_tile.gid - texture.gids.start),
                                         flip: TileFlip {
                                             x: map_tile.flip_h,
                                             y: map_tile.flip_v,
                                             d: map_tile.flip_d,
                                         },
+                                        color,
                                         ..Default::default()
                                     })
-                                    .insert(RoundEntity)
-                                    .id();
-                                tile_storage.set(&tile_pos, Some(tile_entity));
+                                    .insert(RoundEntity);
+
+                                // Animations and damaged variants are tile ids, so they only
+                                // work on atlas textures
+                                if let Some(animation) = animations.get(&map_tile.gid) {
+                                    if texture.atlas {
+                                        tile_entity.insert(animation.clone());
+                                    }
+                                }
+                                if let Some(destructible) = destructibles.get(&map_tile.gid) {
+                                    let mut destructible = destructible.clone();
+                                    if !texture.atlas {
+                                        destructible.damaged_texture = None;
+                                    }
+                                    tile_entity.insert(destructible);
+                                }
+
+                                tile_storage.set(&tile_pos, Some(tile_entity.id()));
+                                empty = false;
                             }
                         }
 
+                        if empty {
+                            commands.entity(layer_entity).despawn();
+                            continue;
+                        }
+
                         commands
                             .entity(layer_entity)
                             .insert_bundle(TilemapBundle {
                                 grid_size,
                                 size: map_size,
                                 storage: tile_storage,
-                                texture: TilemapTexture(
-                                    tiled_map
-                                        .tilesets
-                                        .get(&tileset.first_gid)
-                                        .unwrap()
-                                        .clone_weak(),
-                                ),
-                                tile_size,
-                                spacing: tile_spacing,
-                                transform: bevy_ecs_tilemap::helpers::get_centered_transform_2d(
-                                    &map_size,
-                                    &tile_size,
+                                texture: TilemapTexture(texture.texture.clone()),
+                                tile_size: texture.tile_size,
+                                spacing: texture.spacing,
+                                transform: Transform::from_xyz(
+                                    map_settings.origin.x + offset_x,
+                                    map_settings.origin.y - offset_y,
                                     layer.layer_index as f32,
-                                ) * Transform::from_xyz(offset_x, -offset_y, 0.0),
+                                ),
                                 mesh_type,
                                 ..Default::default()
                             })
                             .insert(RoundEntity);
 
-                        layer_storage.storage.insert(layer.layer_index, layer_entity);
+                        layer_storage
+                            .storage
+                            .entry(layer.layer_index)
+                            .or_default()
+                            .push(layer_entity);
                     }
                 }
             }
         }
     }
 }
+
+/// Animations defined in the tilesets, keyed by the global id of the animated tile
+fn tile_animations(map: &tiled::Map) -> HashMap<u32, TiledAnimation> {
+    let mut animations = HashMap::default();
+    for tileset in map.tilesets.iter() {
+        for tile in tileset.tiles.iter() {
+            let frames = match &tile.animation {
+                Some(frames) if !frames.is_empty() => frames,
+                _ => continue,
+            };
+
+            let frames = frames.iter().map(|f| (f.tile_id, f.duration as f32 / 1000.)).collect();
+            animations.insert(tileset.first_gid + tile.id, TiledAnimation { frames });
+        }
+    }
+    animations
+}
+
+pub fn animate_tiles(time: Res<Time>, mut tiles: Query<(&TiledAnimation, &mut TileTexture)>) {
+    let elapsed = time.seconds_since_startup() as f32;
+
+    for (animation, mut texture) in &mut tiles {
+        let total: f32 = animation.frames.iter().map(|(_, duration)| duration).sum();
+        if total <= 0. {
+            continue;
+        }
+
+        // Every copy of a tile shares the same clock so they stay in sync
+        let mut t = elapsed % total;
+        for (frame, duration) in animation.frames.iter() {
+            if t < *duration {
+                if texture.0 != *frame {
+                    texture.0 = *frame;
+                }
+                break;
+            }
+            t -= duration;
+        }
+    }
+}
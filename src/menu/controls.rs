@@ -44,51 +44,7 @@ pub fn setup_controls_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
                 .with_text_alignment(TextAlignment::CENTER),
             );
 
-            parent.spawn_bundle(
-                TextBundle::from_sections([
-                    TextSection::new(
-                        "Player1:\n",
-                        TextStyle {
-                            font_size: 25.0,
-                            color: Color::WHITE,
-                            font: font_assets.fira_sans.clone(),
-                        },
-                    ),
-                    TextSection::new(
-                        "Movement: WASD\nBoost: LShift\nFire: Space!\n\n",
-                        TextStyle {
-                            font_size: 25.0,
-                            color: Color::WHITE,
-                            font: font_assets.fira_sans.clone(),
-                        },
-                    ),
-                ])
-                .with_text_alignment(TextAlignment::CENTER)
-                .with_style(Style { margin: UiRect::all(Val::Px(16.)), ..Default::default() }),
-            );
-
-            parent.spawn_bundle(
-                TextBundle::from_sections([
-                    TextSection::new(
-                        "Player2:\n",
-                        TextStyle {
-                            font_size: 25.0,
-                            color: Color::WHITE,
-                            font: font_assets.fira_sans.clone(),
-                        },
-                    ),
-                    TextSection::new(
-                        "Movement: Arrow Keys\nBoost: B\nFire: M!\n",
-                        TextStyle {
-                            font_size: 25.0,
-                            color: Color::WHITE,
-                            font: font_assets.fira_sans.clone(),
-                        },
-                    ),
-                ])
-                .with_text_alignment(TextAlignment::CENTER)
-                .with_style(Style { margin: UiRect::all(Val::Px(16.)), ..Default::default() }),
-            );
+            spawn_key_bindings(parent, &font_assets);
 
             parent.spawn_bundle(
                 TextBundle::from_sections([TextSection::new(
@@ -106,15 +62,49 @@ pub fn setup_controls_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
         .insert(ControlsUI);
 }
 
+/// Title line followed by one binding per line
+fn key_binding_text(title: &str, bindings: &[&str], font_assets: &FontAssets) -> TextBundle {
+    let style =
+        TextStyle { font_size: 25.0, color: Color::WHITE, font: font_assets.fira_sans.clone() };
+
+    TextBundle::from_sections(
+        std::iter::once(TextSection::new(format!("{}\n", title), style.clone())).chain(
+            bindings
+                .iter()
+                .map(|binding| TextSection::new(format!("{}\n", binding), style.clone())),
+        ),
+    )
+    .with_text_alignment(TextAlignment::CENTER)
+    .with_style(Style { margin: UiRect::all(Val::Px(16.)), ..Default::default() })
+}
+
+/// Key bindings of both players and the shared keys, also shown in the pause menu
+pub fn spawn_key_bindings(parent: &mut ChildBuilder, font_assets: &FontAssets) {
+    parent.spawn_bundle(key_binding_text(
+        "Player1:",
+        &["Movement: WASD", "Boost: LShift", "Fire: Space!", "Formation: Q"],
+        font_assets,
+    ));
+
+    parent.spawn_bundle(key_binding_text(
+        "Player2:",
+        &["Movement: Arrow Keys", "Boost: B", "Fire: M!", "Formation: N"],
+        font_assets,
+    ));
+
+    parent.spawn_bundle(key_binding_text("Shared:", &["Minimap: Tab", "Pause: Esc"], font_assets));
+}
+
 pub fn btn_listeners(
     audio: Res<Audio>,
     mut commands: Commands,
     keys: Res<Input<KeyCode>>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
 ) {
     if keys.just_pressed(KeyCode::Return) {
-        audio.play(audio_assets.click.clone());
-        commands.insert_resource(NextState(AppState::WorldGen));
+        audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
+        commands.insert_resource(NextState(AppState::MapSelect));
     }
 }
 
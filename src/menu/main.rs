@@ -7,6 +7,7 @@ pub struct MenuMainUI;
 #[derive(Component)]
 pub enum MenuMainBtn {
     PlayGame,
+    Settings,
     Quit,
 }
 
@@ -67,6 +68,35 @@ pub fn setup_main_menu_ui(mut commands: Commands, font_assets: Res<FontAssets>)
                 })
                 .insert(MenuMainBtn::PlayGame);
 
+            // settings button
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(200.0), Val::Px(65.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::from_section(
+                            "Settings",
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: BUTTON_TEXT,
+                            },
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(MenuMainBtn::Settings);
+
             // quit button
             parent
                 .spawn_bundle(ButtonBundle {
@@ -105,16 +135,21 @@ pub fn btn_listeners(
     mut interaction_query: Query<(&Interaction, &MenuMainBtn), Changed<Interaction>>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
 ) {
     for (interaction, btn) in interaction_query.iter_mut() {
         if let Interaction::Clicked = *interaction {
             match btn {
                 MenuMainBtn::PlayGame => {
-                    audio.play(audio_assets.click.clone());
+                    audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
                     commands.insert_resource(NextState(AppState::Controls));
                 }
+                MenuMainBtn::Settings => {
+                    audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
+                    commands.insert_resource(NextState(AppState::Settings));
+                }
                 MenuMainBtn::Quit => {
-                    audio.play(audio_assets.click.clone());
+                    audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
                     exit.send(AppExit);
                 }
             }
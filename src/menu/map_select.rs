@@ -0,0 +1,155 @@
+use crate::menu::*;
+
+#[derive(Component)]
+pub struct MapSelectUI;
+
+/// Index into the map catalogue
+#[derive(Component)]
+pub struct MapSelectBtn(pub usize);
+
+pub fn setup_map_select_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    font_assets: Res<FontAssets>,
+    map_assets: Res<MapAssets>,
+    catalogues: Res<Assets<MapCatalogue>>,
+) {
+    let catalogue = catalogues.get(&map_assets.catalogue).expect("map catalogue is loaded");
+
+    // root node
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::all(Val::Px(0.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_content: AlignContent::Center,
+                align_items: AlignItems::Center,
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_sections([TextSection::new(
+                "Choose a Map",
+                TextStyle {
+                    font_size: 50.0,
+                    color: Color::WHITE,
+                    font: font_assets.fira_sans.clone(),
+                },
+            )]));
+
+            // map cards
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (i, entry) in catalogue.maps.iter().enumerate() {
+                        parent
+                            .spawn_bundle(ButtonBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::ColumnReverse,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(16.)),
+                                    padding: UiRect::all(Val::Px(16.)),
+                                    ..Default::default()
+                                },
+                                color: NORMAL_BUTTON.into(),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn_bundle(ImageBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(192.0), Val::Px(192.0)),
+                                        margin: UiRect::all(Val::Px(8.)),
+                                        ..Default::default()
+                                    },
+                                    image: asset_server.load(entry.preview.as_str()).into(),
+                                    ..Default::default()
+                                });
+
+                                parent.spawn_bundle(
+                                    TextBundle::from_sections([
+                                        TextSection::new(
+                                            format!("{}\n", entry.name),
+                                            TextStyle {
+                                                font: font_assets.fira_sans.clone(),
+                                                font_size: 30.0,
+                                                color: BUTTON_TEXT,
+                                            },
+                                        ),
+                                        TextSection::new(
+                                            format!(
+                                                "Recommended players: {}",
+                                                entry.recommended_players
+                                            ),
+                                            TextStyle {
+                                                font: font_assets.fira_sans.clone(),
+                                                font_size: 20.0,
+                                                color: BUTTON_TEXT,
+                                            },
+                                        ),
+                                    ])
+                                    .with_text_alignment(TextAlignment::CENTER),
+                                );
+                            })
+                            .insert(MapSelectBtn(i));
+                    }
+                });
+        })
+        .insert(MapSelectUI);
+}
+
+pub fn btn_listeners(
+    mut commands: Commands,
+    mut interaction_query: Query<(&Interaction, &MapSelectBtn), Changed<Interaction>>,
+    asset_server: Res<AssetServer>,
+    map_assets: Res<MapAssets>,
+    catalogues: Res<Assets<MapCatalogue>>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
+    rng: Res<RandomNumbers>,
+) {
+    for (interaction, btn) in interaction_query.iter_mut() {
+        if let Interaction::Clicked = *interaction {
+            let catalogue = catalogues.get(&map_assets.catalogue).unwrap();
+            let entry = catalogue.maps[btn.0].clone();
+
+            let (map, seed) = match &entry.source {
+                MapSource::Tiled(path) => (Some(asset_server.load(path.as_str())), 0),
+                MapSource::Generated(arena) => (None, arena.seed.unwrap_or_else(|| rng.next_u64())),
+            };
+
+            audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
+            commands.insert_resource(SelectedMap { entry, map, seed });
+            commands.insert_resource(NextState(AppState::WorldGen));
+        }
+    }
+}
+
+pub struct MapSelectMenuPlugin;
+impl Plugin for MapSelectMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(AppState::MapSelect, setup_map_select_ui)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::MapSelect)
+                    .with_system(btn_visuals::<MapSelectBtn>)
+                    .with_system(btn_listeners)
+                    .into(),
+            )
+            .add_exit_system(AppState::MapSelect, despawn_all_with::<MapSelectUI>);
+    }
+}
@@ -4,10 +4,16 @@ use crate::prelude::*;
 
 pub mod controls;
 pub mod main;
+pub mod map_select;
+pub mod pause;
+pub mod settings;
 pub mod win;
 
 pub use controls::*;
 pub use main::*;
+pub use map_select::*;
+pub use pause::*;
+pub use settings::*;
 pub use win::*;
 
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
@@ -36,6 +42,12 @@ pub fn btn_visuals<BTN: Component>(
 pub struct MenuPlugins;
 impl PluginGroup for MenuPlugins {
     fn build(&mut self, group: &mut bevy::app::PluginGroupBuilder) {
-        group.add(MainMenuPlugin).add(WinMenuPlugin).add(ControlsMenuPlugin);
+        group
+            .add(MainMenuPlugin)
+            .add(WinMenuPlugin)
+            .add(ControlsMenuPlugin)
+            .add(MapSelectMenuPlugin)
+            .add(PauseMenuPlugin)
+            .add(SettingsMenuPlugin);
     }
 }
@@ -0,0 +1,218 @@
+use crate::menu::*;
+
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+
+#[derive(Component)]
+pub struct PauseUI;
+
+#[derive(Component)]
+pub struct PauseControlsUI;
+
+#[derive(Component)]
+pub enum MenuPauseBtn {
+    Resume,
+    Controls,
+    Restart,
+    Quit,
+}
+
+fn pause_overlay() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect::all(Val::Px(0.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            align_content: AlignContent::Center,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        color: OVERLAY_COLOR.into(),
+        ..Default::default()
+    }
+}
+
+pub fn setup_pause_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
+    commands
+        .spawn_bundle(pause_overlay())
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_sections([TextSection::new(
+                "Paused",
+                TextStyle {
+                    font_size: 50.0,
+                    color: Color::WHITE,
+                    font: font_assets.fira_sans.clone(),
+                },
+            )]));
+
+            for (label, btn) in [
+                ("Resume", MenuPauseBtn::Resume),
+                ("Controls", MenuPauseBtn::Controls),
+                ("Restart Round", MenuPauseBtn::Restart),
+                ("Quit to Menu", MenuPauseBtn::Quit),
+            ] {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::all(Val::Px(8.)),
+                            padding: UiRect::all(Val::Px(16.)),
+                            ..Default::default()
+                        },
+                        color: NORMAL_BUTTON.into(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::from_section(
+                                label,
+                                TextStyle {
+                                    font: font_assets.fira_sans.clone(),
+                                    font_size: 40.0,
+                                    color: BUTTON_TEXT,
+                                },
+                            ),
+                            ..Default::default()
+                        });
+                    })
+                    .insert(btn);
+            }
+        })
+        .insert(PauseUI);
+}
+
+pub fn setup_pause_controls_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
+    commands
+        .spawn_bundle(pause_overlay())
+        .with_children(|parent| {
+            spawn_key_bindings(parent, &font_assets);
+
+            parent.spawn_bundle(
+                TextBundle::from_sections([TextSection::new(
+                    "Press 'Esc' to go back...\n",
+                    TextStyle {
+                        font_size: 25.0,
+                        color: Color::WHITE,
+                        font: font_assets.fira_sans.clone(),
+                    },
+                )])
+                .with_text_alignment(TextAlignment::CENTER)
+                .with_style(Style { margin: UiRect::all(Val::Px(16.)), ..Default::default() }),
+            );
+        })
+        .insert(PauseControlsUI);
+}
+
+/// Esc or Start opens the pause menu, and backs out of it
+pub fn toggle_pause(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    state: Res<CurrentState<InGameState>>,
+) {
+    let pressed = keys.just_pressed(KeyCode::Escape)
+        || gamepads.iter().any(|gamepad| {
+            buttons.just_pressed(GamepadButton::new(*gamepad, GamepadButtonType::Start))
+        });
+    if !pressed {
+        return;
+    }
+
+    let next = match state.0 {
+        InGameState::Playing => InGameState::Paused,
+        InGameState::Paused => InGameState::Playing,
+        InGameState::Controls => InGameState::Paused,
+    };
+    commands.insert_resource(NextState(next));
+}
+
+pub fn btn_listeners(
+    mut commands: Commands,
+    mut interaction_query: Query<(&Interaction, &MenuPauseBtn), Changed<Interaction>>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
+) {
+    for (interaction, btn) in interaction_query.iter_mut() {
+        if let Interaction::Clicked = *interaction {
+            audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
+            match btn {
+                MenuPauseBtn::Resume => {
+                    commands.insert_resource(NextState(InGameState::Playing));
+                }
+                MenuPauseBtn::Controls => {
+                    commands.insert_resource(NextState(InGameState::Controls));
+                }
+                // Builds the selected map again from scratch
+                MenuPauseBtn::Restart => {
+                    commands.insert_resource(NextState(AppState::WorldGen));
+                }
+                MenuPauseBtn::Quit => {
+                    commands.insert_resource(NextState(AppState::MenuMain));
+                }
+            }
+        }
+    }
+}
+
+/// Tank engine loops stop with the simulation
+pub fn pause_tank_loops(
+    players: Query<&MusicController>,
+    audio_sinks: Res<Assets<bevy::audio::AudioSink>>,
+) {
+    for music_controller in players.iter() {
+        if let Some(sink) = audio_sinks.get(&music_controller.0) {
+            sink.pause();
+        }
+    }
+}
+
+pub fn resume_tank_loops(
+    app_state: Res<CurrentState<AppState>>,
+    players: Query<&MusicController, (With<Player>, Without<Dead>)>,
+    audio_sinks: Res<Assets<bevy::audio::AudioSink>>,
+) {
+    // Leaving the round also unpauses, the players are on their way out then
+    if app_state.0 != AppState::InGame {
+        return;
+    }
+
+    for music_controller in players.iter() {
+        if let Some(sink) = audio_sinks.get(&music_controller.0) {
+            sink.play();
+        }
+    }
+}
+
+/// Rounds always start unpaused
+pub fn reset_pause(mut commands: Commands) {
+    commands.insert_resource(NextState(InGameState::Playing));
+}
+
+pub struct PauseMenuPlugin;
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_pause.run_in_state(AppState::InGame))
+            .add_exit_system(AppState::InGame, reset_pause);
+
+        app.add_exit_system(InGameState::Playing, pause_tank_loops)
+            .add_enter_system(InGameState::Playing, resume_tank_loops);
+
+        app.add_enter_system(InGameState::Paused, setup_pause_ui)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(InGameState::Paused)
+                    .with_system(btn_visuals::<MenuPauseBtn>)
+                    .with_system(btn_listeners)
+                    .into(),
+            )
+            .add_exit_system(InGameState::Paused, despawn_all_with::<PauseUI>);
+
+        app.add_enter_system(InGameState::Controls, setup_pause_controls_ui)
+            .add_exit_system(InGameState::Controls, despawn_all_with::<PauseControlsUI>);
+    }
+}
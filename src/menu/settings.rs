@@ -0,0 +1,312 @@
+use crate::menu::*;
+
+const VOLUME_STEP: f32 = 0.1;
+const SHAKE_STEP: f32 = 0.25;
+
+#[derive(Component)]
+pub struct SettingsUI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsOption {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    DisplayMode,
+    Resolution,
+    Vsync,
+    ShowFps,
+    ShakeIntensity,
+}
+
+impl SettingsOption {
+    pub const ALL: [SettingsOption; 8] = [
+        SettingsOption::MasterVolume,
+        SettingsOption::MusicVolume,
+        SettingsOption::SfxVolume,
+        SettingsOption::DisplayMode,
+        SettingsOption::Resolution,
+        SettingsOption::Vsync,
+        SettingsOption::ShowFps,
+        SettingsOption::ShakeIntensity,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsOption::MasterVolume => "Master Volume",
+            SettingsOption::MusicVolume => "Music Volume",
+            SettingsOption::SfxVolume => "SFX Volume",
+            SettingsOption::DisplayMode => "Window Mode",
+            SettingsOption::Resolution => "Resolution",
+            SettingsOption::Vsync => "VSync",
+            SettingsOption::ShowFps => "Show FPS",
+            SettingsOption::ShakeIntensity => "Screen Shake",
+        }
+    }
+
+    pub fn value(&self, settings: &GameSettings) -> String {
+        let on_off = |b: bool| if b { "On".to_string() } else { "Off".to_string() };
+        match self {
+            SettingsOption::MasterVolume => format!("{:.0}%", settings.master_volume * 100.),
+            SettingsOption::MusicVolume => format!("{:.0}%", settings.music_volume * 100.),
+            SettingsOption::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.),
+            SettingsOption::DisplayMode => format!("{:?}", settings.display_mode),
+            SettingsOption::Resolution => {
+                format!("{}x{}", settings.resolution.0, settings.resolution.1)
+            }
+            SettingsOption::Vsync => on_off(settings.vsync),
+            SettingsOption::ShowFps => on_off(settings.show_fps),
+            SettingsOption::ShakeIntensity => format!("{:.0}%", settings.shake_intensity * 100.),
+        }
+    }
+
+    /// Moves the option one step up or down, wrapping around for the choices
+    pub fn step(&self, settings: &mut GameSettings, step: i32) {
+        // Rounded so the saved file doesn't pile up float error
+        let volume = |v: f32| ((v / VOLUME_STEP).round() + step as f32) * VOLUME_STEP;
+        let volume = |v: f32| volume(v).clamp(0., 1.);
+        match self {
+            SettingsOption::MasterVolume => settings.master_volume = volume(settings.master_volume),
+            SettingsOption::MusicVolume => settings.music_volume = volume(settings.music_volume),
+            SettingsOption::SfxVolume => settings.sfx_volume = volume(settings.sfx_volume),
+            SettingsOption::DisplayMode => {
+                let modes =
+                    [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Fullscreen];
+                let i = modes.iter().position(|m| *m == settings.display_mode).unwrap_or(0);
+                settings.display_mode = modes[wrap(i, step, modes.len())];
+            }
+            SettingsOption::Resolution => {
+                let i = RESOLUTIONS.iter().position(|r| *r == settings.resolution).unwrap_or(0);
+                settings.resolution = RESOLUTIONS[wrap(i, step, RESOLUTIONS.len())];
+            }
+            SettingsOption::Vsync => settings.vsync = !settings.vsync,
+            SettingsOption::ShowFps => settings.show_fps = !settings.show_fps,
+            SettingsOption::ShakeIntensity => {
+                settings.shake_intensity =
+                    (settings.shake_intensity + step as f32 * SHAKE_STEP).clamp(0., 1.);
+            }
+        }
+    }
+}
+
+fn wrap(index: usize, step: i32, len: usize) -> usize {
+    (index as i32 + step).rem_euclid(len as i32) as usize
+}
+
+#[derive(Component)]
+pub enum SettingsBtn {
+    Change(SettingsOption, i32),
+    Back,
+}
+
+/// Text showing the current value of an option
+#[derive(Component)]
+pub struct SettingsValue(pub SettingsOption);
+
+fn small_button(parent: &mut ChildBuilder, label: &str, btn: SettingsBtn, font: Handle<Font>) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(40.0), Val::Px(40.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(4.)),
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle { font, font_size: 30.0, color: BUTTON_TEXT },
+                ),
+                ..Default::default()
+            });
+        })
+        .insert(btn);
+}
+
+pub fn setup_settings_ui(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    settings: Res<GameSettings>,
+) {
+    // root node
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::all(Val::Px(0.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_content: AlignContent::Center,
+                align_items: AlignItems::Center,
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_sections([TextSection::new(
+                    "Settings",
+                    TextStyle {
+                        font_size: 50.0,
+                        color: Color::WHITE,
+                        font: font_assets.fira_sans.clone(),
+                    },
+                )])
+                .with_style(Style { margin: UiRect::all(Val::Px(16.)), ..Default::default() }),
+            );
+
+            // one row per option: label, <, value, >
+            for option in SettingsOption::ALL {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        color: Color::NONE.into(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(
+                            TextBundle::from_sections([TextSection::new(
+                                option.label(),
+                                TextStyle {
+                                    font_size: 30.0,
+                                    color: Color::WHITE,
+                                    font: font_assets.fira_sans.clone(),
+                                },
+                            )])
+                            .with_style(Style {
+                                size: Size::new(Val::Px(240.0), Val::Auto),
+                                ..Default::default()
+                            }),
+                        );
+
+                        small_button(
+                            parent,
+                            "<",
+                            SettingsBtn::Change(option, -1),
+                            font_assets.fira_sans.clone(),
+                        );
+
+                        parent
+                            .spawn_bundle(
+                                TextBundle::from_sections([TextSection::new(
+                                    option.value(&settings),
+                                    TextStyle {
+                                        font_size: 30.0,
+                                        color: Color::WHITE,
+                                        font: font_assets.fira_sans.clone(),
+                                    },
+                                )])
+                                .with_text_alignment(TextAlignment::CENTER)
+                                .with_style(Style {
+                                    size: Size::new(Val::Px(160.0), Val::Auto),
+                                    justify_content: JustifyContent::Center,
+                                    ..Default::default()
+                                }),
+                            )
+                            .insert(SettingsValue(option));
+
+                        small_button(
+                            parent,
+                            ">",
+                            SettingsBtn::Change(option, 1),
+                            font_assets.fira_sans.clone(),
+                        );
+                    });
+            }
+
+            // back button
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(200.0), Val::Px(65.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::from_section(
+                            "Back",
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: BUTTON_TEXT,
+                            },
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(SettingsBtn::Back);
+        })
+        .insert(SettingsUI);
+}
+
+pub fn settings_btn_listeners(
+    mut commands: Commands,
+    mut interaction_query: Query<(&Interaction, &SettingsBtn), Changed<Interaction>>,
+    mut game_settings: ResMut<GameSettings>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+) {
+    for (interaction, btn) in interaction_query.iter_mut() {
+        if let Interaction::Clicked = *interaction {
+            match btn {
+                SettingsBtn::Change(option, step) => {
+                    option.step(&mut game_settings, *step);
+                }
+                SettingsBtn::Back => {
+                    if let Err(e) = game_settings.save() {
+                        warn!("Failed to save settings: {}", e);
+                    }
+                    commands.insert_resource(NextState(AppState::MenuMain));
+                }
+            }
+            // Played after the change, so the volume sliders can be heard
+            audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
+        }
+    }
+}
+
+pub fn update_settings_values(
+    game_settings: Res<GameSettings>,
+    mut values: Query<(&mut Text, &SettingsValue)>,
+) {
+    if !game_settings.is_changed() {
+        return;
+    }
+
+    for (mut text, value) in values.iter_mut() {
+        text.sections[0].value = value.0.value(&game_settings);
+    }
+}
+
+pub struct SettingsMenuPlugin;
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(AppState::Settings, setup_settings_ui)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Settings)
+                    .with_system(btn_visuals::<SettingsBtn>)
+                    .with_system(settings_btn_listeners)
+                    .with_system(update_settings_values)
+                    .into(),
+            )
+            .add_exit_system(AppState::Settings, despawn_all_with::<SettingsUI>);
+    }
+}
@@ -7,20 +7,116 @@ pub struct WinUI;
 
 #[derive(Component)]
 pub enum MenuWinBtn {
+    Rematch,
     Back,
 }
 
 pub struct MatchData {
     pub result: String,
+    pub winner: usize,
+    pub stats: MatchStats,
+}
+
+const STAT_ROWS: [&str; 8] = [
+    "Zombies collected",
+    "Peak horde",
+    "Kills",
+    "Deaths",
+    "Shots fired",
+    "Accuracy",
+    "Damage dealt",
+    "Boost used",
+];
+
+fn stat_values(stats: &PlayerStats) -> [String; 8] {
+    [
+        stats.zombies_collected.to_string(),
+        stats.peak_horde.to_string(),
+        stats.kills.to_string(),
+        stats.deaths.to_string(),
+        stats.shots_fired.to_string(),
+        format!("{:.0}%", stats.accuracy() * 100.),
+        stats.damage_dealt.to_string(),
+        format!("{:.1}s", stats.boost_used),
+    ]
+}
+
+/// One column of the results table, the header on top
+fn spawn_stat_column(
+    parent: &mut ChildBuilder,
+    header: &str,
+    header_color: Color,
+    rows: &[String],
+    font: Handle<Font>,
+) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                margin: UiRect::new(Val::Px(16.), Val::Px(16.), Val::Px(0.), Val::Px(0.)),
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    header,
+                    TextStyle { font: font.clone(), font_size: 30., color: header_color },
+                ),
+                ..Default::default()
+            });
+            for row in rows {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        row.as_str(),
+                        TextStyle { font: font.clone(), font_size: 25., color: BUTTON_TEXT },
+                    ),
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+fn spawn_win_button(parent: &mut ChildBuilder, label: &str, btn: MenuWinBtn, font: Handle<Font>) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(250.0), Val::Px(65.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(16.)),
+                padding: UiRect::all(Val::Px(16.)),
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle { font, font_size: 40.0, color: BUTTON_TEXT },
+                ),
+                ..Default::default()
+            });
+        })
+        .insert(btn);
 }
 
 pub fn setup_win_ui(
     windows: Res<Windows>,
     mut commands: Commands,
     match_data: Res<MatchData>,
+    selected_map: Res<SelectedMap>,
     font_assets: Res<FontAssets>,
-    mut right_camera: Query<&mut Camera, With<RightCamera>>,
-    mut left_camera: Query<&mut Camera, (With<LeftCamera>, Without<RightCamera>)>,
+    mut right_camera: Query<&mut Camera, (With<RightCamera>, Without<MiniMapCamera>)>,
+    mut left_camera: Query<
+        &mut Camera,
+        (With<LeftCamera>, Without<RightCamera>, Without<MiniMapCamera>),
+    >,
 ) {
     let window = windows.primary();
     let mut left_camera = left_camera.single_mut();
@@ -61,34 +157,80 @@ pub fn setup_win_ui(
                 ),
                 ..Default::default()
             });
-            // back to menu button
+            // map the match was played on, and for how long
+            let duration = match_data.stats.duration as u32;
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    format!(
+                        "Map: {}    Duration: {}:{:02}",
+                        selected_map.entry.name,
+                        duration / 60,
+                        duration % 60
+                    ),
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 25.,
+                        color: BUTTON_TEXT,
+                    },
+                ),
+                ..Default::default()
+            });
+            // results table, one column per player
             parent
-                .spawn_bundle(ButtonBundle {
+                .spawn_bundle(NodeBundle {
                     style: Style {
-                        size: Size::new(Val::Px(250.0), Val::Px(65.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
+                        flex_direction: FlexDirection::Row,
                         margin: UiRect::all(Val::Px(16.)),
-                        padding: UiRect::all(Val::Px(16.)),
                         ..Default::default()
                     },
-                    color: NORMAL_BUTTON.into(),
+                    color: Color::NONE.into(),
                     ..Default::default()
                 })
                 .with_children(|parent| {
-                    parent.spawn_bundle(TextBundle {
-                        text: Text::from_section(
-                            "Back to Menu",
-                            TextStyle {
-                                font: font_assets.fira_sans.clone(),
-                                font_size: 40.0,
-                                color: BUTTON_TEXT,
-                            },
-                        ),
-                        ..Default::default()
-                    });
+                    let labels = STAT_ROWS.map(String::from);
+                    spawn_stat_column(
+                        parent,
+                        "",
+                        BUTTON_TEXT,
+                        &labels,
+                        font_assets.fira_sans.clone(),
+                    );
+
+                    for (handle, stats) in match_data.stats.players.iter().enumerate() {
+                        let mut header = get_color_name(stats.color).to_string();
+                        if handle == match_data.winner {
+                            header.push_str(" (winner)");
+                        }
+                        spawn_stat_column(
+                            parent,
+                            &header,
+                            stats.color,
+                            &stat_values(stats),
+                            font_assets.fira_sans.clone(),
+                        );
+                    }
+                });
+            // rematch and back to menu buttons
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style { flex_direction: FlexDirection::Row, ..Default::default() },
+                    color: Color::NONE.into(),
+                    ..Default::default()
                 })
-                .insert(MenuWinBtn::Back);
+                .with_children(|parent| {
+                    spawn_win_button(
+                        parent,
+                        "Rematch",
+                        MenuWinBtn::Rematch,
+                        font_assets.fira_sans.clone(),
+                    );
+                    spawn_win_button(
+                        parent,
+                        "Main Menu",
+                        MenuWinBtn::Back,
+                        font_assets.fira_sans.clone(),
+                    );
+                });
         })
         .insert(WinUI);
 
@@ -100,13 +242,19 @@ pub fn btn_listeners(
     mut interaction_query: Query<(&Interaction, &MenuWinBtn), Changed<Interaction>>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
 ) {
     for (interaction, btn) in interaction_query.iter_mut() {
         if let Interaction::Clicked = *interaction {
             match btn {
+                // Same map and settings, built again from scratch
+                MenuWinBtn::Rematch => {
+                    commands.insert_resource(NextState(AppState::WorldGen));
+                    audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
+                }
                 MenuWinBtn::Back => {
                     commands.insert_resource(NextState(AppState::MenuMain));
-                    audio.play(audio_assets.click.clone());
+                    audio.play_with_settings(audio_assets.click.clone(), game_settings.sfx(1.0));
                 }
             }
         }
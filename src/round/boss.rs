@@ -0,0 +1,378 @@
+use crate::round::*;
+use bevy::math::Vec3Swizzles;
+
+pub mod boss_settings {
+    use bevy::prelude::Color;
+
+    pub const BOSS_HEALTH: i32 = 40;
+    pub const BOSS_SIZE: f32 = 45.;
+    pub const BOSS_COLOR: Color = Color::MAROON;
+    pub const BOSS_SPEED: f32 = 90.;
+
+    pub const BOSS_SPAWN_INTERVAL: f32 = 120.; // seconds
+    /// Followers a player needs before the boss turns up to contest them
+    pub const BOSS_THRESHOLD_ZOMBIES: usize = 20;
+
+    pub const BOSS_ATTACK_COOLDOWN: f32 = 3.; // seconds
+    pub const BOSS_CHARGE_TIME: f32 = 0.75; // seconds
+    pub const BOSS_CHARGE_SPEED: f32 = 450.;
+    pub const BOSS_SUMMON_COUNT: usize = 5;
+    pub const BOSS_SUMMON_RADIUS: f32 = 60.;
+    pub const BOSS_SLAM_RADIUS: f32 = 120.;
+    pub const BOSS_SLAM_KNOCKBACK: f32 = 80.;
+
+    /// Wild zombies within this radius join whoever kills the boss
+    pub const BOSS_BONUS_RADIUS: f32 = 300.;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Boss Components
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossAttack {
+    Charge,
+    Summon,
+    GroundSlam,
+}
+
+impl BossAttack {
+    pub fn next(&self) -> Self {
+        match self {
+            BossAttack::Charge => BossAttack::Summon,
+            BossAttack::Summon => BossAttack::GroundSlam,
+            BossAttack::GroundSlam => BossAttack::Charge,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Boss {
+    pub next_attack: BossAttack,
+    pub cooldown: Clock,
+    /// Remaining charge time and the direction of the charge
+    pub charge: Option<(f32, Vec2)>,
+    pub charge_hits: Vec<Entity>,
+}
+
+impl Default for Boss {
+    fn default() -> Self {
+        Self {
+            next_attack: BossAttack::Charge,
+            cooldown: Clock::new(boss_settings::BOSS_ATTACK_COOLDOWN),
+            charge: None,
+            charge_hits: Vec::new(),
+        }
+    }
+}
+
+/// Round state used to decide when the next boss shows up
+#[derive(Debug, Default)]
+pub struct BossSpawner {
+    pub timer: f32,
+    pub threshold_triggered: bool,
+}
+
+#[derive(Component)]
+pub struct BossHealthUI;
+
+#[derive(Component)]
+pub struct BossHealthFill;
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn spawn_boss(commands: &mut Commands, transform: Transform, direction_vector: Vec2) -> Entity {
+    let size = zombie_settings::DEFAULT_ZOMBIE_SIZE.1;
+    let boss = spawn_zombie(commands, transform, direction_vector, size);
+
+    // Spawns at zombie size and `zombie_grow` takes it up to boss size
+    commands
+        .entity(boss)
+        .insert(Sprite {
+            color: boss_settings::BOSS_COLOR,
+            custom_size: Some(Vec2::new(size, size)),
+            ..default()
+        })
+        .insert(CreatureSize(boss_settings::BOSS_SIZE))
+        .insert(Health(boss_settings::BOSS_HEALTH))
+        .insert(Tracked::Boss)
+        .insert(Boss::default());
+
+    boss
+}
+
+pub fn setup_boss_spawner(mut commands: Commands) {
+    commands.insert_resource(BossSpawner::default());
+}
+
+pub fn boss_spawner(
+    time: Res<Time>,
+    mut spawner: ResMut<BossSpawner>,
+    bosses: Query<(), With<Boss>>,
+    players: Query<&Player>,
+    mut spawn_events: EventWriter<SpawnEvent>,
+) {
+    if !bosses.is_empty() {
+        return;
+    }
+
+    spawner.timer += time.delta_seconds();
+    let near_win =
+        players.iter().any(|p| p.active_zombies.len() >= boss_settings::BOSS_THRESHOLD_ZOMBIES);
+
+    if spawner.timer >= boss_settings::BOSS_SPAWN_INTERVAL
+        || (near_win && !spawner.threshold_triggered)
+    {
+        spawner.timer = 0.0;
+        spawner.threshold_triggered |= near_win;
+        spawn_events.send(SpawnEvent { spawn_type: SpawnType::Boss, ..default() });
+    }
+}
+
+pub fn boss_ai(
+    time: Res<Time>,
+    mut commands: Commands,
+    map_settings: Res<MapSettings>,
+    collision_map: Res<CollisionMap>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut apply_force_events: EventWriter<ApplyForceEvent>,
+    mut bosses: Query<
+        (Entity, &mut Boss, &mut Transform, &mut crate::components::Direction, &CreatureSize),
+        Without<Player>,
+    >,
+    mut players: Query<(Entity, &Player, &mut Transform), Without<Boss>>,
+    zombies: Query<(Entity, &Transform), (With<CreatureType>, Without<Boss>, Without<Player>)>,
+) {
+    for (boss_ent, mut boss, mut transform, mut direction, size) in &mut bosses {
+        let boss_pos = transform.translation.xy();
+        let nearest = players.iter().map(|(e, _, t)| (e, t.translation.xy())).min_by(|a, b| {
+            a.1.distance_squared(boss_pos).total_cmp(&b.1.distance_squared(boss_pos))
+        });
+
+        // Charging
+        if let Some((remaining, charge_dir)) = boss.charge {
+            let remaining = remaining - time.delta_seconds();
+            transform.translation +=
+                (charge_dir * boss_settings::BOSS_CHARGE_SPEED * time.delta_seconds()).extend(0.);
+
+            for (player_ent, player, player_transform) in players.iter() {
+                let hit_distance = size.0 / 2. + player.size / 2.;
+                if !boss.charge_hits.contains(&player_ent)
+                    && player_transform.translation.xy().distance(boss_pos) < hit_distance
+                {
+                    boss.charge_hits.push(player_ent);
+                    damage_events.send(DamageEvent::new(player_ent, boss_ent));
+                }
+            }
+
+            boss.charge = if remaining > 0.0 { Some((remaining, charge_dir)) } else { None };
+        } else if let Some((_, player_pos)) = nearest {
+            // Lumber towards the nearest player
+            let to_player = (player_pos - boss_pos).normalize_or_zero();
+            direction.0 = to_player;
+            transform.translation +=
+                (to_player * boss_settings::BOSS_SPEED * time.delta_seconds()).extend(0.);
+            transform.rotation = Quat::from_rotation_z(-to_player.x.atan2(to_player.y));
+
+            boss.cooldown.current -= time.delta_seconds();
+            if boss.cooldown.current <= 0.0 {
+                boss.cooldown.reset();
+
+                match boss.next_attack {
+                    BossAttack::Charge => {
+                        boss.charge = Some((boss_settings::BOSS_CHARGE_TIME, to_player));
+                        boss.charge_hits.clear();
+                    }
+                    BossAttack::Summon => {
+                        for i in 0..boss_settings::BOSS_SUMMON_COUNT {
+                            let angle = std::f32::consts::TAU * i as f32
+                                / boss_settings::BOSS_SUMMON_COUNT as f32;
+                            let offset = Vec2::new(angle.sin(), angle.cos());
+                            let position = boss_pos + offset * boss_settings::BOSS_SUMMON_RADIUS;
+
+                            spawn_zombie(
+                                &mut commands,
+                                Transform::from_xyz(position.x, position.y, 10.),
+                                offset,
+                                zombie_settings::DEFAULT_ZOMBIE_SIZE.0,
+                            );
+                        }
+                    }
+                    BossAttack::GroundSlam => {
+                        for (player_ent, _, mut player_transform) in &mut players {
+                            let away = player_transform.translation.xy() - boss_pos;
+                            if away.length() < boss_settings::BOSS_SLAM_RADIUS {
+                                damage_events.send(DamageEvent::new(player_ent, boss_ent));
+                                player_transform.translation += (away.normalize_or_zero()
+                                    * boss_settings::BOSS_SLAM_KNOCKBACK)
+                                    .extend(0.);
+
+                                let clamped = map_settings.clamp(player_transform.translation.xy());
+                                player_transform.translation.x = clamped.x;
+                                player_transform.translation.y = clamped.y;
+                            }
+                        }
+
+                        for (zombie_ent, zombie_transform) in zombies.iter() {
+                            let away = zombie_transform.translation.xy() - boss_pos;
+                            if away.length() < boss_settings::BOSS_SLAM_RADIUS {
+                                apply_force_events.send(ApplyForceEvent(
+                                    zombie_ent,
+                                    away.normalize_or_zero(),
+                                    zombie_settings::ZOMBIE_CHASE,
+                                ));
+                            }
+                        }
+                    }
+                }
+
+                boss.next_attack = boss.next_attack.next();
+            }
+        }
+
+        // Walls stop charges as well
+        let end = collision_map.slide(boss_pos, transform.translation.xy(), size.0 / 2.);
+        transform.translation.x = end.x;
+        transform.translation.y = end.y;
+
+        // Clamp to map bounds
+        let clamped = map_settings.clamp(transform.translation.xy());
+        transform.translation.x = clamped.x;
+        transform.translation.y = clamped.y;
+    }
+}
+
+pub fn reward_boss_kill(
+    mut commands: Commands,
+    rng: Res<RandomNumbers>,
+    mut events: EventReader<BossKilledEvent>,
+    mut players: Query<(&mut Player, &mut Boost)>,
+    mut wild: Query<
+        (Entity, &Transform, &mut CreatureType, &mut Sprite),
+        (Without<CreatureFollow>, Without<CreatureTarget>, Without<Boss>, Without<Player>),
+    >,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
+) {
+    for BossKilledEvent { killer, position } in events.iter() {
+        let (mut player, mut boost) = match players.get_mut(*killer) {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+
+        for (zombie_ent, transform, mut c_type, mut sprite) in &mut wild {
+            if transform.translation.xy().distance(*position) > boss_settings::BOSS_BONUS_RADIUS {
+                continue;
+            }
+
+            let follow_distance = rng.range(
+                zombie_settings::FOLLOW_PLAYER_MIN_DISTANCE,
+                zombie_settings::FOLLOW_PLAYER_MAX_DISTANCE,
+            );
+
+            player.active_zombies.push(zombie_ent);
+            c_type.0 = Some(*killer);
+            sprite.color = player.color;
+            commands
+                .entity(zombie_ent)
+                .insert(CreatureFollow(follow_distance))
+                .insert(FormationSlot::default());
+        }
+
+        boost.0 = player_settings::BOOST_MAX;
+        audio.play_with_settings(audio_assets.victory.clone(), game_settings.sfx(1.0));
+    }
+}
+
+pub fn spawn_boss_health_ui(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    new_bosses: Query<(), Added<Boss>>,
+) {
+    if new_bosses.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect { top: Val::Px(30.0), left: Val::Percent(30.), ..default() },
+                size: Size::new(Val::Percent(40.), Val::Px(12.)),
+                ..default()
+            },
+            color: Color::rgba(0.1, 0.1, 0.1, 0.8).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    color: boss_settings::BOSS_COLOR.into(),
+                    ..default()
+                })
+                .insert(BossHealthFill);
+        })
+        .insert(BossHealthUI)
+        .insert(RoundEntity);
+
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "Zombie Boss",
+                TextStyle { font_size: 15.0, color: Color::WHITE, font: fonts.fira_sans.clone() },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect { top: Val::Px(10.0), left: Val::Percent(30.), ..default() },
+                ..default()
+            }),
+        )
+        .insert(BossHealthUI)
+        .insert(RoundEntity);
+}
+
+pub fn update_boss_health_ui(
+    mut commands: Commands,
+    bosses: Query<&Health, With<Boss>>,
+    mut fills: Query<&mut Style, With<BossHealthFill>>,
+    ui: Query<Entity, With<BossHealthUI>>,
+) {
+    match bosses.iter().next() {
+        Some(health) => {
+            let percent = health.0.max(0) as f32 / boss_settings::BOSS_HEALTH as f32 * 100.;
+            for mut style in &mut fills {
+                style.size.width = Val::Percent(percent);
+            }
+        }
+        None => {
+            for e in ui.iter() {
+                commands.entity(e).despawn_recursive();
+            }
+        }
+    }
+}
+
+pub struct BossPlugin;
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BossKilledEvent>();
+
+        app.add_enter_system(AppState::InGame, setup_boss_spawner);
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(boss_spawner)
+                .with_system(boss_ai)
+                .with_system(reward_boss_kill)
+                .with_system(spawn_boss_health_ui)
+                .with_system(update_boss_health_ui)
+                .into(),
+        );
+    }
+}
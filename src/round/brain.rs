@@ -0,0 +1,102 @@
+use crate::round::*;
+use bevy::math::Vec3Swizzles;
+
+pub mod brain_settings {
+    pub const BRAIN_SIZE: f32 = 12.;
+    pub const BRAIN_DECAY_TIME: f32 = 10.; // seconds
+    pub const BRAIN_FADE_TIME: f32 = 3.; // seconds
+    pub const BRAIN_COLLECTION_DISTANCE: f32 = 25.;
+    pub const BOOST_PER_BRAIN: f32 = 10.;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Brain Components
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Component, Debug)]
+pub struct Brain;
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn spawn_brain(commands: &mut Commands, texture: Handle<Image>, position: Vec2) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform::from_xyz(position.x, position.y, 9.),
+            texture,
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(
+                    brain_settings::BRAIN_SIZE,
+                    brain_settings::BRAIN_SIZE,
+                )),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Brain)
+        .insert(Tracked::Pickup)
+        .insert(Clock::new(brain_settings::BRAIN_DECAY_TIME))
+        .insert(RoundEntity)
+        .id()
+}
+
+pub fn decay_brains(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut brains: Query<(Entity, &mut Clock, &mut Sprite), With<Brain>>,
+) {
+    for (brain, mut clock, mut sprite) in &mut brains {
+        clock.current -= time.delta_seconds();
+
+        if clock.current <= 0.0 {
+            commands.entity(brain).despawn_recursive();
+        } else if clock.current < brain_settings::BRAIN_FADE_TIME {
+            sprite.color.set_a(clock.current / brain_settings::BRAIN_FADE_TIME);
+        }
+    }
+}
+
+pub fn collect_brains(
+    mut commands: Commands,
+    mut players: Query<(&mut Player, &Transform, &mut Boost)>,
+    brains: Query<(Entity, &Transform), (With<Brain>, Without<Player>)>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
+) {
+    let mut collected = Vec::new();
+    for (mut player, transform, mut boost) in &mut players {
+        for (brain, brain_transform) in brains.iter() {
+            let distance =
+                Vec2::distance(transform.translation.xy(), brain_transform.translation.xy());
+            if collected.contains(&brain)
+                || distance >= brain_settings::BRAIN_COLLECTION_DISTANCE + player.size / 2.
+            {
+                continue;
+            }
+
+            collected.push(brain);
+            commands.entity(brain).despawn_recursive();
+
+            player.brains += 1;
+            boost.0 += brain_settings::BOOST_PER_BRAIN;
+            boost.0 = boost.0.clamp(0.0, player_settings::BOOST_MAX);
+
+            audio.play_with_settings(audio_assets.collect.clone(), game_settings.sfx(0.5));
+        }
+    }
+}
+
+pub struct BrainPlugin;
+impl Plugin for BrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .label(SystemLabels::Collection)
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(decay_brains)
+                .with_system(collect_brains)
+                .into(),
+        );
+    }
+}
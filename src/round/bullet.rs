@@ -1,4 +1,5 @@
 use crate::round::*;
+use bevy::math::Vec3Swizzles;
 
 ///////////////////////////////////////////////////////////////////////////////
 // Bullet Components
@@ -14,6 +15,7 @@ pub struct FiredBy(pub Entity);
 
 pub const BULLET_SPEED: f32 = 600.;
 pub const BULLET_FLIGHT_TIME: f32 = 3.;
+pub const BULLET_STRUCTURE_DAMAGE: u32 = 1;
 
 pub fn reload_bullet(mut query: Query<(&PlayerControls, &mut BulletReady)>) {
     for (controls, mut bullet_ready) in query.iter_mut() {
@@ -29,6 +31,7 @@ pub fn fire_bullets(
     mut player_query: Query<(Entity, &Transform, &Player, &PlayerControls, &mut BulletReady)>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
 ) {
     for (player_ent, transform, player, controls, mut bullet_ready) in player_query.iter_mut() {
         if controls.firing && bullet_ready.0 {
@@ -49,7 +52,7 @@ pub fn fire_bullets(
 
             bullet_ready.0 = false;
 
-            audio.play(audio_assets.laser.clone());
+            audio.play_with_settings(audio_assets.laser.clone(), game_settings.sfx(1.0));
         }
     }
 }
@@ -57,13 +60,22 @@ pub fn fire_bullets(
 pub fn move_bullet(
     time: Res<Time>,
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &mut Clock), With<Bullet>>,
+    collision_map: Res<CollisionMap>,
+    mut structure_events: EventWriter<StructureDamageEvent>,
+    mut query: Query<(Entity, &mut Transform, &mut Clock, &FiredBy), With<Bullet>>,
 ) {
-    for (bullet, mut t, mut bullet_timer) in query.iter_mut() {
+    for (bullet, mut t, mut bullet_timer, fired_by) in query.iter_mut() {
         apply_forward_delta(&time, &mut t, BULLET_SPEED, 1.0);
         bullet_timer.current -= time.delta_seconds();
 
-        if bullet_timer.current <= 0.0 {
+        if collision_map.is_solid(t.translation.xy()) {
+            structure_events.send(StructureDamageEvent {
+                tile: collision_map.world_to_tile(t.translation.xy()),
+                damage: BULLET_STRUCTURE_DAMAGE,
+                attacker: fired_by.0,
+            });
+            commands.entity(bullet).despawn_recursive();
+        } else if bullet_timer.current <= 0.0 {
             commands.entity(bullet).despawn_recursive();
         }
     }
@@ -76,6 +88,7 @@ impl Plugin for BulletPlugin {
             ConditionSet::new()
                 .label(SystemLabels::BulletReload)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(reload_bullet)
                 .into(),
         );
@@ -86,6 +99,8 @@ impl Plugin for BulletPlugin {
                 .after(SystemLabels::PlayerMove)
                 .after(SystemLabels::BulletReload)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .run_if_not(hit_stop_active)
                 .with_system(fire_bullets)
                 .with_system(move_bullet)
                 .into(),
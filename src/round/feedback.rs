@@ -0,0 +1,244 @@
+use crate::round::*;
+use bevy::{
+    math::Vec3Swizzles,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::RenderLayers,
+    },
+};
+
+pub mod feedback_settings {
+    use bevy::prelude::Color;
+
+    pub const TRAUMA_PER_HIT: f32 = 0.35;
+    pub const TRAUMA_ON_DEATH: f32 = 0.8;
+    pub const TRAUMA_DECAY: f32 = 1.5; // per second
+    pub const MAX_SHAKE_OFFSET: f32 = 12.;
+    pub const MAX_SHAKE_ANGLE: f32 = 0.04; // radians
+
+    pub const VIGNETTE_COLOR: Color = Color::rgba(0.85, 0.0, 0.0, 0.0);
+    pub const VIGNETTE_ALPHA: f32 = 0.6;
+    pub const VIGNETTE_FADE_TIME: f32 = 0.35; // seconds
+    pub const VIGNETTE_TEXTURE_SIZE: u32 = 64;
+
+    pub const KILL_HIT_STOP: f32 = 0.05; // seconds
+    pub const PLAYER_KILL_HIT_STOP: f32 = 0.12; // seconds
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Feedback Components
+///////////////////////////////////////////////////////////////////////////////
+
+/// Gameplay movement is frozen while this runs down
+#[derive(Default, Debug)]
+pub struct HitStop {
+    pub remaining: f32,
+}
+
+impl HitStop {
+    pub fn trigger(&mut self, duration: f32) {
+        self.remaining = self.remaining.max(duration);
+    }
+}
+
+pub fn hit_stop_active(hit_stop: Res<HitStop>) -> bool {
+    hit_stop.remaining > 0.
+}
+
+/// Accumulated shake of a game camera, in 0..=1
+#[derive(Component, Default, Debug)]
+pub struct Trauma(pub f32);
+
+#[derive(Component, Debug)]
+pub struct Vignette;
+
+pub struct VignetteTexture(pub Handle<Image>);
+
+impl FromWorld for VignetteTexture {
+    fn from_world(world: &mut World) -> Self {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        Self(images.add(vignette_image()))
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Transparent in the middle, opaque towards the corners
+fn vignette_image() -> Image {
+    let size = feedback_settings::VIGNETTE_TEXTURE_SIZE;
+    let half = size as f32 / 2.;
+
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let offset = (Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - half) / half;
+            let alpha = ((offset.length() - 0.5) / 0.9).clamp(0., 1.);
+            data.extend_from_slice(&[255, 255, 255, (alpha * alpha * 255.) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+pub fn setup_feedback(
+    mut commands: Commands,
+    texture: Res<VignetteTexture>,
+    cameras: Query<(Entity, Option<&RightCamera>), (With<CameraFollow>, Without<MiniMapCamera>)>,
+) {
+    for (camera, right) in cameras.iter() {
+        let layer = if right.is_some() { RIGHT_VIEW_LAYER } else { LEFT_VIEW_LAYER };
+        let vignette = commands
+            .spawn_bundle(SpriteBundle {
+                // Just in front of the camera, above everything else
+                transform: Transform::from_xyz(0., 0., -1.),
+                texture: texture.0.clone(),
+                sprite: Sprite { color: feedback_settings::VIGNETTE_COLOR, ..default() },
+                ..default()
+            })
+            .insert(Vignette)
+            .insert(RenderLayers::layer(layer))
+            .insert(RoundEntity)
+            .id();
+
+        commands.entity(camera).insert(Trauma::default()).add_child(vignette);
+    }
+}
+
+/// Shakes and flashes the camera of whichever player got hurt, and briefly freezes on kills
+pub fn hit_feedback(
+    mut damages: EventReader<DamageEvent>,
+    mut hit_stop: ResMut<HitStop>,
+    victims: Query<(&Health, Option<&Player>)>,
+    mut cameras: Query<(&mut Trauma, &Children, Option<&RightCamera>)>,
+    mut vignettes: Query<&mut Sprite, With<Vignette>>,
+) {
+    for DamageEvent { victim, .. } in damages.iter() {
+        let (health, player) = match victims.get(*victim) {
+            Ok(victim) => victim,
+            Err(_) => continue,
+        };
+
+        // Runs after `handle_damage_events`, so health is already down
+        let died = health.0 <= 0;
+        if died {
+            hit_stop.trigger(if player.is_some() {
+                feedback_settings::PLAYER_KILL_HIT_STOP
+            } else {
+                feedback_settings::KILL_HIT_STOP
+            });
+        }
+
+        let player = match player {
+            Some(player) => player,
+            None => continue,
+        };
+
+        let trauma_amount = if died {
+            feedback_settings::TRAUMA_ON_DEATH
+        } else {
+            feedback_settings::TRAUMA_PER_HIT
+        };
+
+        for (mut trauma, children, right) in cameras.iter_mut() {
+            let handle = if right.is_some() { 1 } else { 0 };
+            if handle != player.handle {
+                continue;
+            }
+
+            trauma.0 = (trauma.0 + trauma_amount).min(1.);
+            for child in children.iter() {
+                if let Ok(mut sprite) = vignettes.get_mut(*child) {
+                    sprite.color.set_a(feedback_settings::VIGNETTE_ALPHA);
+                }
+            }
+        }
+    }
+}
+
+pub fn screen_shake(
+    time: Res<Time>,
+    rng: Res<RandomNumbers>,
+    settings: Res<GameSettings>,
+    mut cameras: Query<(&mut Transform, &mut Trauma, &CameraFollow)>,
+) {
+    for (mut t, mut trauma, follow) in cameras.iter_mut() {
+        trauma.0 = (trauma.0 - feedback_settings::TRAUMA_DECAY * time.delta_seconds()).max(0.);
+
+        // Shake grows with the square of trauma so small hits stay subtle
+        let shake = trauma.0 * trauma.0 * settings.shake_intensity.clamp(0., 1.);
+        let center = follow.center.unwrap_or_else(|| t.translation.xy());
+        let offset = Vec2::new(rng.range(-1., 1.), rng.range(-1., 1.))
+            * feedback_settings::MAX_SHAKE_OFFSET
+            * shake;
+
+        t.translation.x = center.x + offset.x;
+        t.translation.y = center.y + offset.y;
+        t.rotation =
+            Quat::from_rotation_z(rng.range(-1., 1.) * feedback_settings::MAX_SHAKE_ANGLE * shake);
+    }
+}
+
+/// Fades the vignette out and keeps it covering the whole view as the camera zooms
+pub fn update_vignettes(
+    time: Res<Time>,
+    cameras: Query<(&Camera, &OrthographicProjection, &Children)>,
+    mut vignettes: Query<&mut Sprite, With<Vignette>>,
+) {
+    let fade = feedback_settings::VIGNETTE_ALPHA / feedback_settings::VIGNETTE_FADE_TIME;
+
+    for (camera, projection, children) in cameras.iter() {
+        let view_size = camera.logical_viewport_size().unwrap_or(Vec2::ZERO) * projection.scale;
+
+        for child in children.iter() {
+            if let Ok(mut sprite) = vignettes.get_mut(*child) {
+                let alpha = (sprite.color.a() - fade * time.delta_seconds()).max(0.);
+                sprite.color.set_a(alpha);
+                sprite.custom_size = Some(view_size);
+            }
+        }
+    }
+}
+
+pub fn tick_hit_stop(time: Res<Time>, mut hit_stop: ResMut<HitStop>) {
+    if hit_stop.remaining > 0. {
+        hit_stop.remaining = (hit_stop.remaining - time.delta_seconds()).max(0.);
+    }
+}
+
+pub fn reset_feedback(
+    mut hit_stop: ResMut<HitStop>,
+    mut cameras: Query<(&mut Transform, &mut CameraFollow)>,
+) {
+    hit_stop.remaining = 0.;
+    for (mut t, mut follow) in cameras.iter_mut() {
+        t.rotation = Quat::IDENTITY;
+        follow.center = None;
+    }
+}
+
+pub struct FeedbackPlugin;
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitStop>().init_resource::<VignetteTexture>();
+
+        app.add_enter_system(AppState::InGame, setup_feedback);
+        app.add_system_set(
+            ConditionSet::new()
+                .after(SystemLabels::CameraMove)
+                .after(SystemLabels::HandleDamage)
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(hit_feedback)
+                .with_system(screen_shake)
+                .with_system(update_vignettes)
+                .with_system(tick_hit_stop)
+                .into(),
+        )
+        .add_exit_system(AppState::InGame, reset_feedback);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::round::*;
+use bevy::{math::Vec3Swizzles, utils::HashSet};
+use std::f32::consts::TAU;
+
+pub mod formation_settings {
+    pub const FORMATION_SPACING: f32 = 20.;
+    pub const FORMATION_ARRIVE_DISTANCE: f32 = 8.;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Formation Components
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Formation {
+    #[default]
+    Ring,
+    Wedge,
+    Column,
+    Shell,
+}
+
+impl Formation {
+    pub fn next(&self) -> Self {
+        match self {
+            Formation::Ring => Formation::Wedge,
+            Formation::Wedge => Formation::Column,
+            Formation::Column => Formation::Shell,
+            Formation::Shell => Formation::Ring,
+        }
+    }
+
+    /// Offset of the `index`th follower out of `count`, relative to the player.
+    /// +Y is the direction the player is facing.
+    pub fn slot(&self, index: usize, count: usize) -> Vec2 {
+        use formation_settings::FORMATION_SPACING;
+        use zombie_settings::{FOLLOW_PLAYER_MAX_DISTANCE, FOLLOW_PLAYER_MIN_DISTANCE};
+
+        match self {
+            // One loose circle which widens as the horde grows
+            Formation::Ring => {
+                let radius =
+                    f32::max(FOLLOW_PLAYER_MAX_DISTANCE, count as f32 * FORMATION_SPACING / TAU);
+                let angle = TAU * index as f32 / count.max(1) as f32;
+                Vec2::new(angle.sin(), angle.cos()) * radius
+            }
+            // Rows of 1, 3, 5, ... with the tip pointing ahead of the player
+            Formation::Wedge => {
+                let rows = (count as f32).sqrt().ceil() as usize;
+                let row = (index as f32).sqrt().floor() as usize;
+                let column = index - row * row;
+
+                let x = (column as f32 - row as f32) * FORMATION_SPACING;
+                let y = FOLLOW_PLAYER_MIN_DISTANCE + (rows - row) as f32 * FORMATION_SPACING;
+                Vec2::new(x, y)
+            }
+            // Two abreast, trailing behind the player
+            Formation::Column => {
+                let x = ((index % 2) as f32 - 0.5) * FORMATION_SPACING;
+                let y = -(FOLLOW_PLAYER_MIN_DISTANCE + (index / 2) as f32 * FORMATION_SPACING);
+                Vec2::new(x, y)
+            }
+            // Tightly packed layers, filling the innermost layer first
+            Formation::Shell => {
+                let mut radius = FOLLOW_PLAYER_MIN_DISTANCE;
+                let mut first = 0;
+                loop {
+                    let capacity = ((TAU * radius) / FORMATION_SPACING).floor() as usize;
+                    let in_layer = usize::min(capacity, count - first);
+                    if index < first + in_layer {
+                        let angle = TAU * (index - first) as f32 / in_layer as f32;
+                        return Vec2::new(angle.sin(), angle.cos()) * radius;
+                    }
+
+                    first += in_layer;
+                    radius += FORMATION_SPACING;
+                }
+            }
+        }
+    }
+}
+
+/// Offset from the owning player this follower steers to
+#[derive(Component, Debug, Default, Clone)]
+pub struct FormationSlot(pub Vec2);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// World position of a formation slot for a player at `player_transform`
+pub fn slot_position(player_transform: &Transform, slot: Vec2) -> Vec2 {
+    player_transform.translation.xy() + (player_transform.rotation * slot.extend(0.)).xy()
+}
+
+pub fn cycle_formation(mut players: Query<(&mut Player, &PlayerControls)>) {
+    for (mut player, controls) in &mut players {
+        if controls.formation {
+            player.formation = player.formation.next();
+        }
+    }
+}
+
+pub fn assign_formation_slots(
+    players: Query<(Entity, &Player, ChangeTrackers<Player>)>,
+    mut slots: Query<(&mut FormationSlot, &CreatureType, ChangeTrackers<FormationSlot>)>,
+) {
+    // A new follower's slot is inserted through commands, so it only shows up the frame after
+    // its owner changed
+    let new_followers = slots
+        .iter()
+        .filter(|(_, _, changes)| changes.is_added())
+        .filter_map(|(_, c_type, _)| c_type.0)
+        .collect::<HashSet<_>>();
+
+    for (player_ent, player, changes) in players.iter() {
+        if !changes.is_changed() && !new_followers.contains(&player_ent) {
+            continue;
+        }
+
+        let count = player.active_zombies.len();
+        for (i, zombie) in player.active_zombies.iter().enumerate() {
+            if let Ok((mut slot, _, _)) = slots.get_mut(*zombie) {
+                slot.0 = player.formation.slot(i, count);
+            }
+        }
+    }
+}
+
+pub struct FormationPlugin;
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .after(SystemLabels::Input)
+                .after(SystemLabels::Collection)
+                .before(SystemLabels::ApplyForce)
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(cycle_formation)
+                .with_system(assign_formation_slots)
+                .into(),
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use crate::round::*;
+use bevy::{
+    math::Vec3Swizzles,
+    render::{texture::DEFAULT_IMAGE_HANDLE, view::RenderLayers},
+    utils::HashMap,
+};
+
+pub mod indicator_settings {
+    use bevy::prelude::Color;
+
+    /// Distance kept from the viewport edge, in screen pixels
+    pub const EDGE_MARGIN: f32 = 30.;
+    pub const INDICATOR_Z: f32 = 900.;
+
+    pub const ARROW_SIZE: f32 = 16.;
+    pub const ARROW_OFFSET: f32 = 16.;
+    pub const ICON_SIZE: f32 = 14.;
+    pub const FONT_SIZE: f32 = 12.;
+
+    /// Pickups further away than this aren't worth pointing at
+    pub const PICKUP_RANGE: f32 = 600.;
+
+    pub const BOSS_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+    pub const PICKUP_COLOR: Color = Color::rgb(1.0, 0.6, 0.8);
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Indicator Components
+///////////////////////////////////////////////////////////////////////////////
+
+/// Marks an entity worth pointing at when it's off-screen
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tracked {
+    /// Shown to the other players
+    Player,
+    Boss,
+    Pickup,
+    /// Shown to the player the creature follows, when it strays off-screen
+    Follower,
+}
+
+/// Pinned to the edge of the viewport of player `view`, pointing at `target`
+#[derive(Component, Debug)]
+pub struct EdgeIndicator {
+    pub target: Entity,
+    pub view: usize,
+}
+
+#[derive(Component)]
+pub struct IndicatorArrow;
+
+#[derive(Component)]
+pub struct IndicatorDistance;
+
+///////////////////////////////////////////////////////////////////////////////
+
+fn spawn_indicator(
+    commands: &mut Commands,
+    indicator: EdgeIndicator,
+    transform: Transform,
+    textures: &TextureAssets,
+    icon: Option<Handle<Image>>,
+    color: Color,
+    font: Handle<Font>,
+) {
+    let layers =
+        RenderLayers::layer(if indicator.view == 1 { RIGHT_VIEW_LAYER } else { LEFT_VIEW_LAYER });
+
+    commands
+        .spawn_bundle(SpatialBundle::from_transform(transform))
+        .insert(indicator)
+        .insert(RoundEntity)
+        .with_children(|p| {
+            p.spawn_bundle(SpriteBundle {
+                texture: textures.arrow.clone(),
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(indicator_settings::ARROW_SIZE)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(IndicatorArrow)
+            .insert(layers);
+
+            // Icons are tinted like the arrow, plain squares stand in for creatures
+            p.spawn_bundle(SpriteBundle {
+                texture: icon.unwrap_or_else(|| DEFAULT_IMAGE_HANDLE.typed()),
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(indicator_settings::ICON_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..default()
+            })
+            .insert(layers);
+
+            p.spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle { font, font_size: indicator_settings::FONT_SIZE, color },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(0., -indicator_settings::ICON_SIZE, 1.),
+                ..default()
+            })
+            .insert(IndicatorDistance)
+            .insert(layers);
+        });
+}
+
+/// Keeps one indicator per off-screen target and viewport, pinned to the viewport edge
+pub fn update_edge_indicators(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    cameras: Query<
+        (&Camera, &OrthographicProjection, &CameraFollow, Option<&RightCamera>),
+        Without<MiniMapCamera>,
+    >,
+    targets: Query<
+        (Entity, &Tracked, &Transform, Option<&Player>, Option<&CreatureType>),
+        Without<Dead>,
+    >,
+    mut indicators: Query<(Entity, &EdgeIndicator, &mut Transform, &Children), Without<Tracked>>,
+    mut arrows: Query<
+        &mut Transform,
+        (With<IndicatorArrow>, Without<EdgeIndicator>, Without<Tracked>),
+    >,
+    mut texts: Query<&mut Text, With<IndicatorDistance>>,
+) {
+    let mut stale = indicators
+        .iter()
+        .map(|(e, indicator, ..)| ((indicator.target, indicator.view), e))
+        .collect::<HashMap<_, _>>();
+
+    for (camera, projection, follow, right) in cameras.iter() {
+        let view = if right.is_some() { 1 } else { 0 };
+        let (center, viewport) = match (follow.center, camera.logical_viewport_size()) {
+            (Some(center), Some(viewport)) => (center, viewport),
+            _ => continue,
+        };
+        let viewer = targets.iter().find(|(.., p, _)| p.map_or(false, |p| p.handle == view));
+        let viewer_ent = viewer.map(|(e, ..)| e);
+
+        let half_view = viewport * projection.scale / 2.;
+        let inner = (half_view - indicator_settings::EDGE_MARGIN * projection.scale).max(Vec2::ONE);
+
+        for (target, tracked, t, player, c_type) in targets.iter() {
+            let offset = t.translation.xy() - center;
+            if offset.x.abs() <= half_view.x && offset.y.abs() <= half_view.y {
+                continue;
+            }
+
+            let (color, icon) = match tracked {
+                Tracked::Player => match player {
+                    Some(player) if Some(target) != viewer_ent => {
+                        (player.color, Some(textures.tank.clone()))
+                    }
+                    _ => continue,
+                },
+                Tracked::Boss => (indicator_settings::BOSS_COLOR, None),
+                Tracked::Pickup if offset.length() <= indicator_settings::PICKUP_RANGE => {
+                    (indicator_settings::PICKUP_COLOR, Some(textures.brain.clone()))
+                }
+                Tracked::Follower => match (viewer, c_type) {
+                    (Some((owner, .., Some(p), _)), Some(c_type)) if c_type.0 == Some(owner) => {
+                        (p.color, None)
+                    }
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            // Shrink the offset until it sits on the inner rect of the view
+            let edge = center + offset * (inner.x / offset.x.abs()).min(inner.y / offset.y.abs());
+            let direction = offset.normalize_or_zero();
+            // Constant size on screen, whatever the zoom
+            let root = Transform::from_translation(edge.extend(indicator_settings::INDICATOR_Z))
+                .with_scale(Vec3::splat(projection.scale));
+
+            let indicator = match stale.remove(&(target, view)) {
+                Some(indicator) => indicator,
+                None => {
+                    // Children are pointed at the target from next frame on
+                    spawn_indicator(
+                        &mut commands,
+                        EdgeIndicator { target, view },
+                        root,
+                        &textures,
+                        icon,
+                        color,
+                        fonts.fira_sans.clone(),
+                    );
+                    continue;
+                }
+            };
+
+            if let Ok((_, _, mut transform, children)) = indicators.get_mut(indicator) {
+                *transform = root;
+
+                for child in children.iter() {
+                    if let Ok(mut arrow) = arrows.get_mut(*child) {
+                        arrow.translation =
+                            (direction * indicator_settings::ARROW_OFFSET).extend(0.);
+                        arrow.rotation = Quat::from_rotation_arc(Vec3::Y, direction.extend(0.));
+                    }
+                    if let Ok(mut text) = texts.get_mut(*child) {
+                        let distance = (offset.length() / MAP_TILE_SIZE as f32).round();
+                        text.sections[0].value = format!("{}m", distance);
+                    }
+                }
+            }
+        }
+    }
+
+    // Targets that are gone or back on screen
+    for indicator in stale.into_values() {
+        commands.entity(indicator).despawn_recursive();
+    }
+}
+
+pub struct IndicatorPlugin;
+impl Plugin for IndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            update_edge_indicators.run_in_state(AppState::InGame).after(SystemLabels::CameraMove),
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub const INPUT_LEFT: u8 = 1 << 2;
 pub const INPUT_RIGHT: u8 = 1 << 3;
 pub const INPUT_FIRE: u8 = 1 << 4;
 pub const INPUT_SHIFT: u8 = 1 << 5;
+pub const INPUT_FORMATION: u8 = 1 << 6;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Pod, Zeroable)]
@@ -34,6 +35,9 @@ pub fn input(keyboard_input: Res<Input<KeyCode>>) -> [u8; 2] {
     if GameKey::LocalShift.pressed(&keyboard_input) {
         left_inp |= INPUT_SHIFT;
     }
+    if GameKey::LocalFormation.just_pressed(&keyboard_input) {
+        left_inp |= INPUT_FORMATION;
+    }
 
     // Right Player
     if GameKey::Up.pressed(&keyboard_input) {
@@ -54,6 +58,9 @@ pub fn input(keyboard_input: Res<Input<KeyCode>>) -> [u8; 2] {
     if GameKey::Shift.pressed(&keyboard_input) {
         right_inp |= INPUT_SHIFT;
     }
+    if GameKey::Formation.just_pressed(&keyboard_input) {
+        right_inp |= INPUT_FORMATION;
+    }
 
     // GameInput(left_inp, right_inp)
     [left_inp, right_inp]
@@ -81,6 +88,7 @@ pub fn apply_inputs(In(inputs): In<[u8; 2]>, mut query: Query<(&mut PlayerContro
 
         c.firing = input & INPUT_FIRE != 0;
         c.shift = (input & INPUT_SHIFT != 0) && boost.0 > 0.0;
+        c.formation = input & INPUT_FORMATION != 0;
     }
 }
 
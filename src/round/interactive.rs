@@ -0,0 +1,423 @@
+use crate::round::*;
+use bevy::math::Vec3Swizzles;
+
+pub mod interactive_settings {
+    use bevy::prelude::Color;
+
+    pub const TELEPORTER_COLOR: Color = Color::rgba(0.55, 0.25, 0.95, 0.6);
+    pub const DOOR_COLOR: Color = Color::rgb(0.45, 0.3, 0.15);
+    pub const DOOR_OPEN_ALPHA: f32 = 0.25;
+    pub const PLATE_COLOR: Color = Color::rgba(0.6, 0.6, 0.6, 0.8);
+    pub const PLATE_PRESSED_COLOR: Color = Color::rgba(0.35, 0.75, 0.35, 0.8);
+    pub const HAZARD_COLOR: Color = Color::rgba(1.0, 0.35, 0.0, 0.5);
+    pub const BARREL_COLOR: Color = Color::rgb(0.8, 0.1, 0.1);
+
+    pub const EXPLOSION_COLOR: Color = Color::rgba(1.0, 0.6, 0.1, 0.6);
+    pub const EXPLOSION_FADE_TIME: f32 = 0.4; // seconds
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Interactive Object Components
+///////////////////////////////////////////////////////////////////////////////
+
+/// Area covered by a map object
+#[derive(Component, Debug)]
+pub struct ObjectRect(pub SpawnRect);
+
+#[derive(Component, Debug)]
+pub struct Teleporter {
+    /// Center of the paired teleporter, if it has one
+    pub destination: Option<Vec2>,
+    pub cooldown: f32,
+}
+
+/// Keeps a teleported entity from bouncing straight back
+#[derive(Component, Debug)]
+pub struct TeleportCooldown(pub f32);
+
+#[derive(Component, Debug)]
+pub struct Door {
+    pub id: String,
+    pub open: bool,
+    /// Tiles the door blocks while closed
+    pub tiles: Vec<(i32, i32)>,
+}
+
+#[derive(Component, Debug)]
+pub struct PressurePlate {
+    pub door: String,
+    pub hold: bool,
+    pub pressed: bool,
+}
+
+#[derive(Component, Debug)]
+pub struct Hazard {
+    pub damage_interval: f32,
+    pub tick: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct ExplosiveBarrel {
+    pub radius: f32,
+    pub damage: u32,
+    pub knockback: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct ExplosionFlash;
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn spawn_map_objects(
+    mut commands: Commands,
+    map_objects: Res<MapObjects>,
+    mut collision_map: ResMut<CollisionMap>,
+) {
+    use interactive_settings::*;
+
+    for object in map_objects.0.iter() {
+        let rect = object.rect;
+        let center = rect.center();
+
+        let mut color = match object.kind {
+            MapObjectKind::Teleporter { .. } => TELEPORTER_COLOR,
+            MapObjectKind::Door { .. } => DOOR_COLOR,
+            MapObjectKind::PressurePlate { .. } => PLATE_COLOR,
+            MapObjectKind::Hazard { .. } => HAZARD_COLOR,
+            MapObjectKind::ExplosiveBarrel { .. } => BARREL_COLOR,
+        };
+        if let MapObjectKind::Door { open: true, .. } = object.kind {
+            color.set_a(DOOR_OPEN_ALPHA);
+        }
+
+        let mut entity = commands.spawn_bundle(SpriteBundle {
+            transform: Transform::from_xyz(center.x, center.y, 8.),
+            sprite: Sprite { color, custom_size: Some(rect.size()), ..default() },
+            ..default()
+        });
+        entity.insert(ObjectRect(rect)).insert(RoundEntity);
+
+        match &object.kind {
+            MapObjectKind::Teleporter { pair, cooldown } => {
+                let destination = map_objects
+                    .0
+                    .iter()
+                    .find(|other| match &other.kind {
+                        MapObjectKind::Teleporter { pair: other_pair, .. } => {
+                            other_pair == pair && other.rect != rect
+                        }
+                        _ => false,
+                    })
+                    .map(|other| other.rect.center());
+                if destination.is_none() {
+                    warn!("Teleporter '{}' has no partner for pair '{}'", object.name, pair);
+                }
+
+                entity.insert(Teleporter { destination, cooldown: *cooldown });
+            }
+            MapObjectKind::Door { id, open } => {
+                let tiles = collision_map.tiles_in_rect(rect.min, rect.max);
+                if !open {
+                    collision_map.set_blocked(&tiles, true);
+                }
+                entity.insert(Door { id: id.clone(), open: *open, tiles });
+            }
+            MapObjectKind::PressurePlate { door, hold } => {
+                entity.insert(PressurePlate { door: door.clone(), hold: *hold, pressed: false });
+            }
+            MapObjectKind::Hazard { damage_interval } => {
+                entity.insert(Hazard { damage_interval: *damage_interval, tick: 0. });
+            }
+            MapObjectKind::ExplosiveBarrel { radius, damage, knockback } => {
+                entity.insert(ExplosiveBarrel {
+                    radius: *radius,
+                    damage: *damage,
+                    knockback: *knockback,
+                });
+            }
+        }
+    }
+}
+
+/// Radius of anything that can walk onto map objects
+fn body_radius(player: Option<&Player>, size: Option<&CreatureSize>) -> f32 {
+    match (player, size) {
+        (Some(player), _) => player.size / 2.,
+        (_, Some(size)) => size.0 / 2.,
+        _ => 0.,
+    }
+}
+
+pub fn use_teleporters(
+    mut commands: Commands,
+    teleporters: Query<(&Teleporter, &ObjectRect)>,
+    mut bodies: ParamSet<(
+        Query<
+            (Entity, &Transform, Option<&Player>, Option<&CreatureSize>),
+            (
+                Or<(With<Player>, With<CreatureType>)>,
+                Without<TeleportCooldown>,
+                Without<CreatureFollow>,
+                Without<Boss>,
+            ),
+        >,
+        Query<&mut Transform>,
+    )>,
+    collision_map: Res<CollisionMap>,
+) {
+    let mut teleports = Vec::new();
+    for (entity, transform, player, size) in bodies.p0().iter() {
+        let position = transform.translation.xy();
+        let radius = body_radius(player, size);
+
+        for (teleporter, rect) in teleporters.iter() {
+            if let Some(destination) = teleporter.destination {
+                if rect.0.overlaps(position, radius) {
+                    // Players bring their horde along
+                    let followers = player.map(|p| p.active_zombies.clone()).unwrap_or_default();
+                    teleports.push((entity, position, destination, followers, teleporter.cooldown));
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut transforms = bodies.p1();
+    for (entity, from, destination, followers, cooldown) in teleports {
+        for follower in followers.into_iter().chain(std::iter::once(entity)) {
+            if let Ok(mut transform) = transforms.get_mut(follower) {
+                let mut target = destination + (transform.translation.xy() - from);
+                if collision_map.collides(target, 1.) {
+                    target = destination;
+                }
+
+                transform.translation.x = target.x;
+                transform.translation.y = target.y;
+                commands.entity(follower).insert(TeleportCooldown(cooldown));
+            }
+        }
+    }
+}
+
+pub fn tick_teleport_cooldowns(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut cooldowns: Query<(Entity, &mut TeleportCooldown)>,
+) {
+    for (entity, mut cooldown) in &mut cooldowns {
+        cooldown.0 -= time.delta_seconds();
+        if cooldown.0 <= 0. {
+            commands.entity(entity).remove::<TeleportCooldown>();
+        }
+    }
+}
+
+pub fn update_pressure_plates(
+    mut plates: Query<(&mut PressurePlate, &ObjectRect, &mut Sprite), Without<Door>>,
+    mut doors: Query<(&mut Door, &mut Sprite), Without<PressurePlate>>,
+    bodies: Query<
+        (&Transform, Option<&Player>, Option<&CreatureSize>, Option<&CreatureType>),
+        Or<(With<Player>, With<CreatureType>)>,
+    >,
+    mut collision_map: ResMut<CollisionMap>,
+) {
+    use interactive_settings::*;
+
+    for (mut plate, rect, mut sprite) in &mut plates {
+        // Only players and their hordes, wild zombies wandering around don't work the doors
+        let pressed = bodies
+            .iter()
+            .filter(|(_, player, _, c_type)| {
+                player.is_some() || c_type.map_or(false, |c| c.0.is_some())
+            })
+            .any(|(transform, player, size, _)| {
+                rect.0.overlaps(transform.translation.xy(), body_radius(player, size))
+            });
+        if pressed == plate.pressed {
+            continue;
+        }
+
+        plate.pressed = pressed;
+        sprite.color = if pressed { PLATE_PRESSED_COLOR } else { PLATE_COLOR };
+
+        // Toggle plates only act when stepped on
+        if !plate.hold && !pressed {
+            continue;
+        }
+
+        for (mut door, mut door_sprite) in &mut doors {
+            if door.id != plate.door {
+                continue;
+            }
+
+            let open = if plate.hold { pressed } else { !door.open };
+            if open == door.open {
+                continue;
+            }
+
+            door.open = open;
+            door_sprite.color.set_a(if open { DOOR_OPEN_ALPHA } else { DOOR_COLOR.a() });
+            collision_map.set_blocked(&door.tiles, !open);
+        }
+    }
+}
+
+pub fn damage_from_hazards(
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut hazards: Query<(Entity, &mut Hazard, &ObjectRect)>,
+    victims: Query<(Entity, &Transform, Option<&Player>, Option<&CreatureSize>), With<Health>>,
+) {
+    for (hazard_ent, mut hazard, rect) in &mut hazards {
+        hazard.tick -= time.delta_seconds();
+        if hazard.tick > 0. {
+            continue;
+        }
+        hazard.tick = hazard.damage_interval;
+
+        for (victim, transform, player, size) in victims.iter() {
+            if rect.0.overlaps(transform.translation.xy(), body_radius(player, size)) {
+                damage_events.send(DamageEvent::new(victim, hazard_ent));
+            }
+        }
+    }
+}
+
+pub fn explode_barrels(
+    mut commands: Commands,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut hit_events: EventWriter<BulletHitEvent>,
+    mut apply_force_events: EventWriter<ApplyForceEvent>,
+    map_settings: Res<MapSettings>,
+    collision_map: Res<CollisionMap>,
+    bullets: Query<(Entity, &Transform, &FiredBy), With<Bullet>>,
+    barrels: Query<(Entity, &ExplosiveBarrel, &ObjectRect)>,
+    mut players: Query<(Entity, &Player, &mut Transform), Without<Bullet>>,
+    creatures: Query<(Entity, &Transform), (With<Health>, Without<Player>, Without<Bullet>)>,
+) {
+    // Barrels shot this frame, and the player to credit for the damage
+    let mut exploding = Vec::new();
+    for (bullet, bullet_transform, fired_by) in bullets.iter() {
+        for (barrel, _, rect) in barrels.iter() {
+            if !exploding.iter().any(|(b, _)| *b == barrel)
+                && rect.0.contains(bullet_transform.translation.xy())
+            {
+                commands.entity(bullet).despawn_recursive();
+                hit_events.send(BulletHitEvent { shooter: fired_by.0 });
+                exploding.push((barrel, fired_by.0));
+                break;
+            }
+        }
+    }
+
+    // Explosions set off any barrel caught in the blast
+    let mut i = 0;
+    while i < exploding.len() {
+        let (barrel_ent, attacker) = exploding[i];
+        i += 1;
+
+        let (_, barrel, rect) = barrels.get(barrel_ent).unwrap();
+        let center = rect.0.center();
+        commands.entity(barrel_ent).despawn_recursive();
+        spawn_explosion_flash(&mut commands, center, barrel.radius);
+
+        for (other, _, other_rect) in barrels.iter() {
+            if !exploding.iter().any(|(b, _)| *b == other)
+                && other_rect.0.overlaps(center, barrel.radius)
+            {
+                exploding.push((other, attacker));
+            }
+        }
+
+        for (player_ent, player, mut transform) in &mut players {
+            let away = transform.translation.xy() - center;
+            if away.length() > barrel.radius + player.size / 2. {
+                continue;
+            }
+
+            for _ in 0..barrel.damage {
+                damage_events.send(DamageEvent::new(player_ent, attacker));
+            }
+
+            let start = transform.translation.xy();
+            let end = collision_map.slide(
+                start,
+                start + away.normalize_or_zero() * barrel.knockback,
+                player.size / 2.,
+            );
+            let end = map_settings.clamp(end);
+            transform.translation.x = end.x;
+            transform.translation.y = end.y;
+        }
+
+        for (creature, transform) in creatures.iter() {
+            let away = transform.translation.xy() - center;
+            if away.length() > barrel.radius {
+                continue;
+            }
+
+            for _ in 0..barrel.damage {
+                damage_events.send(DamageEvent::new(creature, attacker));
+            }
+            apply_force_events.send(ApplyForceEvent(
+                creature,
+                away.normalize_or_zero(),
+                zombie_settings::ZOMBIE_CHASE,
+            ));
+        }
+    }
+}
+
+fn spawn_explosion_flash(commands: &mut Commands, center: Vec2, radius: f32) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform::from_xyz(center.x, center.y, 11.),
+            sprite: Sprite {
+                color: interactive_settings::EXPLOSION_COLOR,
+                custom_size: Some(Vec2::splat(radius * 2.)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ExplosionFlash)
+        .insert(Clock::new(interactive_settings::EXPLOSION_FADE_TIME))
+        .insert(RoundEntity);
+}
+
+pub fn fade_explosions(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut flashes: Query<(Entity, &mut Clock, &mut Sprite), With<ExplosionFlash>>,
+) {
+    for (flash, mut clock, mut sprite) in &mut flashes {
+        clock.current -= time.delta_seconds();
+        if clock.current <= 0. {
+            commands.entity(flash).despawn_recursive();
+        } else {
+            let alpha = interactive_settings::EXPLOSION_COLOR.a();
+            sprite.color.set_a(alpha * clock.current / interactive_settings::EXPLOSION_FADE_TIME);
+        }
+    }
+}
+
+pub struct InteractivePlugin;
+impl Plugin for InteractivePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(AppState::InGame, spawn_map_objects);
+
+        app.add_system_set(
+            ConditionSet::new()
+                .after(SystemLabels::PlayerMove)
+                .after(SystemLabels::BulletMove)
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(use_teleporters)
+                .with_system(tick_teleport_cooldowns)
+                .with_system(update_pressure_plates)
+                .with_system(damage_from_hazards)
+                .with_system(explode_barrels)
+                .with_system(fade_explosions)
+                .into(),
+        );
+    }
+}
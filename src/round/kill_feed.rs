@@ -0,0 +1,217 @@
+use crate::round::*;
+use bevy::render::view::RenderLayers;
+
+pub mod kill_feed_settings {
+    /// Horde sizes announced once per round, on the way to `COLLECTED_ZOMBIES_TO_WIN`
+    pub const MILESTONES: [usize; 4] = [5, 10, 15, 20];
+
+    pub const ENTRY_LIFETIME: f32 = 5.; // seconds
+    pub const FADE_TIME: f32 = 1.; // seconds
+    pub const MAX_ENTRIES: usize = 5;
+
+    /// Distance kept from the top right corner of the viewport, in screen pixels
+    pub const FEED_MARGIN: f32 = 12.;
+    pub const ROW_HEIGHT: f32 = 18.;
+    pub const FONT_SIZE: f32 = 14.;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Kill Feed Components
+///////////////////////////////////////////////////////////////////////////////
+
+/// One line of the feed, counting down on its `Clock`
+#[derive(Component, Debug)]
+pub struct FeedEntry;
+
+/// Per-player progress used to spot milestones and lead changes
+#[derive(Default, Debug)]
+pub struct RoundStandings {
+    pub milestones: Vec<usize>,
+    pub leader: Option<usize>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn reset_standings(mut commands: Commands) {
+    commands.insert_resource(RoundStandings::default());
+}
+
+/// Horde sizes only ever show up on the players, so milestones and the lead are derived from them
+pub fn track_standings(
+    mut standings: ResMut<RoundStandings>,
+    mut round_events: EventWriter<RoundEvent>,
+    players: Query<&Player>,
+) {
+    for player in players.iter() {
+        if standings.milestones.len() <= player.handle {
+            standings.milestones.resize(player.handle + 1, 0);
+        }
+
+        let horde = player.active_zombies.len();
+        let reached = kill_feed_settings::MILESTONES.iter().rev().find(|m| horde >= **m);
+        if let Some(&milestone) = reached {
+            if milestone > standings.milestones[player.handle] {
+                standings.milestones[player.handle] = milestone;
+                round_events
+                    .send(RoundEvent::MilestoneReached { player: player.handle, horde: milestone });
+            }
+        }
+    }
+
+    // Only a strict lead counts, ties keep whoever was ahead before
+    let mut hordes = players.iter().map(|p| (p.handle, p.active_zombies.len())).collect::<Vec<_>>();
+    hordes.sort_by(|a, b| b.1.cmp(&a.1));
+    if let [(leader, first), rest @ ..] = hordes.as_slice() {
+        let strict = rest.first().map_or(true, |(_, second)| first > second);
+        if strict && *first > 0 && standings.leader != Some(*leader) {
+            standings.leader = Some(*leader);
+            round_events.send(RoundEvent::LeadChanged { leader: *leader });
+        }
+    }
+}
+
+/// Feed line for an event, players are named after their colour
+fn describe(event: &RoundEvent, players: &Query<&Player>) -> (String, Color) {
+    let player = |handle: usize| players.iter().find(|p| p.handle == handle);
+    let name = |handle: usize| player(handle).map_or("Someone", |p| get_color_name(p.color));
+    let color = |handle: usize| player(handle).map_or(Color::WHITE, |p| p.color);
+
+    match event {
+        RoundEvent::PlayerKilled { victim, cause } => {
+            let text = match cause {
+                KillCause::Player(killer) if killer == victim => {
+                    format!("{} took themselves out", name(*victim))
+                }
+                KillCause::Player(killer) => format!("{} killed {}", name(*killer), name(*victim)),
+                KillCause::Zombies => format!("{} was overrun by zombies", name(*victim)),
+                KillCause::Boss => format!("{} was crushed by the boss", name(*victim)),
+                KillCause::Hazard => format!("{} was killed by the map", name(*victim)),
+            };
+            let killer = match cause {
+                KillCause::Player(killer) => color(*killer),
+                _ => color(*victim),
+            };
+            (text, killer)
+        }
+        RoundEvent::HordeStolen { from, by, count } => {
+            let zombies = if *count == 1 { "zombie" } else { "zombies" };
+            (format!("{} stole {} {} from {}", name(*by), count, zombies, name(*from)), color(*by))
+        }
+        RoundEvent::BossSpawned => {
+            ("A boss has appeared!".to_string(), indicator_settings::BOSS_COLOR)
+        }
+        RoundEvent::BossKilled { killer: Some(killer) } => {
+            (format!("{} killed the boss", name(*killer)), color(*killer))
+        }
+        RoundEvent::BossKilled { killer: None } => {
+            ("The boss is dead".to_string(), indicator_settings::BOSS_COLOR)
+        }
+        RoundEvent::MilestoneReached { player, horde } => {
+            (format!("{} has {} zombies", name(*player), horde), color(*player))
+        }
+        RoundEvent::LeadChanged { leader } => {
+            (format!("{} takes the lead", name(*leader)), color(*leader))
+        }
+    }
+}
+
+/// Every event gets a line at the top of the feed in each viewport
+pub fn spawn_feed_entries(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut round_events: EventReader<RoundEvent>,
+    players: Query<&Player>,
+    huds: Query<(Entity, &Hud)>,
+) {
+    for event in round_events.iter() {
+        let (text, color) = describe(event, &players);
+
+        for (hud_ent, hud) in huds.iter() {
+            let layers =
+                RenderLayers::layer(if hud.view == 1 { RIGHT_VIEW_LAYER } else { LEFT_VIEW_LAYER });
+            let mut bundle = hud_text(
+                &text,
+                fonts.fira_sans.clone(),
+                kill_feed_settings::FONT_SIZE,
+                HudAlign::TopRight,
+            );
+            bundle.text.sections[0].style.color = color;
+
+            let entry = commands
+                .spawn_bundle(bundle)
+                .insert(FeedEntry)
+                .insert(Clock::new(kill_feed_settings::ENTRY_LIFETIME))
+                .insert(HudAnchor {
+                    view: hud.view,
+                    offset: Vec2::splat(-kill_feed_settings::FEED_MARGIN),
+                    align: HudAlign::TopRight,
+                })
+                .insert(layers)
+                .id();
+
+            commands.entity(hud_ent).add_child(entry);
+        }
+    }
+}
+
+/// Stacks the newest lines on top, fading the old ones out
+pub fn update_kill_feed(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut entries: Query<(Entity, &mut Clock, &mut HudAnchor, &mut Text), With<FeedEntry>>,
+) {
+    let mut rows = [0; 2];
+
+    let mut sorted = entries.iter_mut().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| b.1.current.total_cmp(&a.1.current));
+
+    for (entity, mut clock, mut anchor, mut text) in sorted {
+        clock.current -= time.delta_seconds();
+
+        let row = &mut rows[anchor.view.min(1)];
+        if clock.current <= 0. || *row >= kill_feed_settings::MAX_ENTRIES {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        anchor.offset = Vec2::new(
+            -kill_feed_settings::FEED_MARGIN,
+            -kill_feed_settings::FEED_MARGIN - *row as f32 * kill_feed_settings::ROW_HEIGHT,
+        );
+        *row += 1;
+
+        let alpha = (clock.current / kill_feed_settings::FADE_TIME).min(1.);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
+
+/// Plain log of the round, in place of the old debug prints
+pub fn log_round_events(mut round_events: EventReader<RoundEvent>) {
+    for event in round_events.iter() {
+        info!("{:?}", event);
+    }
+}
+
+pub struct KillFeedPlugin;
+impl Plugin for KillFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundStandings>();
+
+        app.add_enter_system(AppState::InGame, reset_standings);
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(track_standings)
+                .with_system(update_kill_feed)
+                .into(),
+        )
+        .add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::InGame)
+                .with_system(spawn_feed_entries)
+                .with_system(log_round_events)
+                .into(),
+        );
+    }
+}
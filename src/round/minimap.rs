@@ -0,0 +1,226 @@
+use crate::round::*;
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    render::{camera::Viewport, view::RenderLayers},
+    ui::UiCameraConfig,
+    window::{WindowId, WindowResized},
+};
+
+pub mod minimap_settings {
+    use bevy::prelude::{Color, KeyCode};
+
+    /// Markers only live on this layer, so the game cameras never see them
+    pub const MINIMAP_LAYER: u8 = 1;
+    pub const TOGGLE_KEY: KeyCode = KeyCode::Tab;
+
+    /// Share of the smaller viewport side taken by the minimap
+    pub const MINIMAP_FRACTION: f32 = 0.28;
+    pub const MINIMAP_MARGIN: u32 = 10; // physical pixels
+    pub const BACKGROUND_COLOR: Color = Color::rgba(0.05, 0.05, 0.05, 1.0);
+
+    // Marker sizes are in world units, the minimap shrinks the whole arena
+    pub const PLAYER_MARKER_SIZE: f32 = 70.;
+    pub const BOSS_MARKER_SIZE: f32 = 80.;
+    pub const FOLLOWER_MARKER_SIZE: f32 = 28.;
+    pub const ZOMBIE_MARKER_SIZE: f32 = 22.;
+    pub const BRAIN_MARKER_SIZE: f32 = 30.;
+
+    pub const ZOMBIE_MARKER_COLOR: Color = Color::rgba(0.6, 0.8, 0.6, 0.6);
+    pub const BOSS_MARKER_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+    pub const BRAIN_MARKER_COLOR: Color = Color::rgb(1.0, 0.6, 0.8);
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Minimap Components
+///////////////////////////////////////////////////////////////////////////////
+
+pub struct MiniMapVisible(pub bool);
+
+impl Default for MiniMapVisible {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiniMapMarker {
+    Player,
+    Boss,
+    Zombie,
+    Brain,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// One minimap per viewport, tagged with the same side marker as the game camera it sits on
+pub fn spawn_minimap_cameras(mut commands: Commands, map_settings: Res<MapSettings>) {
+    let center = map_settings.center();
+
+    for priority in [2, 3] {
+        let mut camera = commands.spawn_bundle(Camera2dBundle {
+            transform: Transform::from_xyz(center.x, center.y, 999.),
+            camera: Camera { priority, ..default() },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(minimap_settings::BACKGROUND_COLOR),
+            },
+            ..default()
+        });
+
+        camera
+            .insert(MiniMapCamera)
+            .insert(RenderLayers::from_layers(&[0, minimap_settings::MINIMAP_LAYER]))
+            // HUD text would otherwise be drawn again inside the minimap
+            .insert(UiCameraConfig { show_ui: false })
+            .insert(RoundEntity);
+
+        if priority == 2 {
+            camera.insert(LeftCamera);
+        } else {
+            camera.insert(RightCamera);
+        }
+    }
+}
+
+/// Keeps each minimap in the bottom right corner of its viewport, sized to fit the whole arena
+pub fn update_minimap_viewports(
+    windows: Res<Windows>,
+    visible: Res<MiniMapVisible>,
+    map_settings: Res<MapSettings>,
+    mut resize_events: EventReader<WindowResized>,
+    mut minimaps: Query<
+        (&mut Camera, &mut OrthographicProjection, Option<&RightCamera>),
+        With<MiniMapCamera>,
+    >,
+    added: Query<(), Added<MiniMapCamera>>,
+) {
+    let resized = resize_events.iter().any(|e| e.id == WindowId::primary());
+    if !resized && !visible.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let window = windows.primary();
+    let half_width = window.physical_width() / 2;
+    let height = window.physical_height();
+
+    let side = (half_width.min(height) as f32 * minimap_settings::MINIMAP_FRACTION) as u32;
+    let aspect = map_settings.width / map_settings.height;
+    let size = if aspect >= 1. {
+        UVec2::new(side, (side as f32 / aspect) as u32)
+    } else {
+        UVec2::new((side as f32 * aspect) as u32, side)
+    }
+    .max(UVec2::ONE);
+
+    for (mut camera, mut projection, right) in &mut minimaps {
+        camera.is_active = visible.0;
+
+        let offset = if right.is_some() { half_width } else { 0 };
+        let margin = minimap_settings::MINIMAP_MARGIN;
+        camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(
+                (offset + half_width).saturating_sub(size.x + margin),
+                height.saturating_sub(size.y + margin),
+            ),
+            physical_size: size,
+            ..default()
+        });
+
+        // The projection works in logical pixels
+        let logical_size = size.as_vec2() / window.scale_factor() as f32;
+        projection.scale =
+            (map_settings.width / logical_size.x).max(map_settings.height / logical_size.y);
+    }
+}
+
+pub fn toggle_minimap(keys: Res<Input<KeyCode>>, mut visible: ResMut<MiniMapVisible>) {
+    if keys.just_pressed(minimap_settings::TOGGLE_KEY) {
+        visible.0 = !visible.0;
+    }
+}
+
+/// Gives everything worth showing on the minimap a marker child on the minimap layer
+pub fn add_minimap_markers(
+    mut commands: Commands,
+    players: Query<(Entity, &Player), Added<Player>>,
+    bosses: Query<Entity, Added<Boss>>,
+    creatures: Query<Entity, (Added<CreatureType>, Without<Boss>, Without<Player>)>,
+    brains: Query<Entity, Added<Brain>>,
+) {
+    let markers = players
+        .iter()
+        .map(|(e, p)| (e, MiniMapMarker::Player, p.color, minimap_settings::PLAYER_MARKER_SIZE))
+        .chain(bosses.iter().map(|e| {
+            let color = minimap_settings::BOSS_MARKER_COLOR;
+            (e, MiniMapMarker::Boss, color, minimap_settings::BOSS_MARKER_SIZE)
+        }))
+        .chain(creatures.iter().map(|e| {
+            let color = minimap_settings::ZOMBIE_MARKER_COLOR;
+            (e, MiniMapMarker::Zombie, color, minimap_settings::ZOMBIE_MARKER_SIZE)
+        }))
+        .chain(brains.iter().map(|e| {
+            let color = minimap_settings::BRAIN_MARKER_COLOR;
+            (e, MiniMapMarker::Brain, color, minimap_settings::BRAIN_MARKER_SIZE)
+        }))
+        .collect::<Vec<_>>();
+
+    for (entity, marker, color, size) in markers {
+        let marker = commands
+            .spawn_bundle(SpriteBundle {
+                // Drawn above the map and the creatures themselves
+                transform: Transform::from_xyz(0., 0., 50.),
+                sprite: Sprite { color, custom_size: Some(Vec2::splat(size)), ..default() },
+                ..default()
+            })
+            .insert(marker)
+            .insert(RenderLayers::layer(minimap_settings::MINIMAP_LAYER))
+            .id();
+
+        commands.entity(entity).add_child(marker);
+    }
+}
+
+/// Followers take their player's colour, so hordes show up as clusters
+pub fn update_zombie_markers(
+    players: Query<&Player>,
+    creatures: Query<(&CreatureType, &Children), Changed<CreatureType>>,
+    mut markers: Query<(&MiniMapMarker, &mut Sprite)>,
+) {
+    for (c_type, children) in &creatures {
+        let owner = c_type.0.and_then(|p| players.get(p).ok());
+
+        for child in children.iter() {
+            if let Ok((MiniMapMarker::Zombie, mut sprite)) = markers.get_mut(*child) {
+                match owner {
+                    Some(player) => {
+                        sprite.color = player.color;
+                        sprite.custom_size =
+                            Some(Vec2::splat(minimap_settings::FOLLOWER_MARKER_SIZE));
+                    }
+                    None => {
+                        sprite.color = minimap_settings::ZOMBIE_MARKER_COLOR;
+                        sprite.custom_size =
+                            Some(Vec2::splat(minimap_settings::ZOMBIE_MARKER_SIZE));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct MiniMapPlugin;
+impl Plugin for MiniMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MiniMapVisible>();
+
+        app.add_enter_system(AppState::InGame, spawn_minimap_cameras);
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::InGame)
+                .with_system(toggle_minimap)
+                .with_system(update_minimap_viewports)
+                .with_system(add_minimap_markers)
+                .with_system(update_zombie_markers)
+                .into(),
+        );
+    }
+}
@@ -6,16 +6,34 @@ use bevy::{
 };
 use bytemuck::{Pod, Zeroable};
 
+mod boss;
+mod brain;
 mod bullet;
+mod feedback;
+mod formation;
+mod indicators;
 mod input;
+mod interactive;
+mod kill_feed;
+mod minimap;
 mod player;
+mod stats;
 mod ui;
 mod zombie;
 
+pub use boss::*;
+pub use brain::*;
 pub use bullet::*;
+pub use feedback::*;
+pub use formation::*;
+pub use indicators::*;
 pub use input::*;
+pub use interactive::*;
+pub use kill_feed::*;
+pub use minimap::*;
 pub use player::*;
 use rand::{seq::SliceRandom, thread_rng};
+pub use stats::*;
 pub use ui::*;
 pub use zombie::*;
 
@@ -26,11 +44,6 @@ pub const COLLECTED_ZOMBIES_TO_WIN: usize = 25;
 #[derive(Component)]
 pub struct RoundEntity;
 
-#[derive(Component)]
-pub struct SnapToPlayer(pub usize);
-
-pub struct RotateToP1(pub Quat);
-
 pub fn setup_round(
     windows: Res<Windows>,
     mut commands: Commands,
@@ -64,6 +77,7 @@ pub fn setup_round(
             handle: Some(i),
             color: Some(*color),
             spawn_type: SpawnType::Player,
+            ..default()
         });
     }
 
@@ -71,37 +85,6 @@ pub fn setup_round(
         .for_each(|_| spawn_events.send(SpawnEvent { spawn_type: SpawnType::Zombie, ..default() }));
 }
 
-pub fn snap_to_player(
-    p: Query<(&Player, &Transform), With<Player>>,
-    mut q: Query<(&mut Transform, &SnapToPlayer), Without<Player>>,
-) {
-    let players = p.iter().collect::<Vec<_>>();
-    if players.len() == 2 {
-        for (mut t, s) in q.iter_mut() {
-            // Snap to player 0
-            if s.0 == 0 {
-                let p1 = players[0];
-                let p2 = players[1];
-
-                let player_translation = p1.1.translation.xy();
-                let to_player = (player_translation - p2.1.translation.xy()).normalize();
-                let rotate_to_player = Quat::from_rotation_arc(Vec3::Y, to_player.extend(0.));
-                t.rotation = rotate_to_player;
-            }
-            // snap to player 1
-            else {
-                let p1 = players[1];
-                let p2 = players[0];
-
-                let player_translation = p1.1.translation.xy();
-                let to_player = (player_translation - p2.1.translation.xy()).normalize();
-                let rotate_to_player = Quat::from_rotation_arc(Vec3::Y, to_player.extend(0.));
-                t.rotation = rotate_to_player;
-            }
-        }
-    }
-}
-
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// Spawning
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -112,20 +95,31 @@ pub fn spawning(
     meshes: Res<MeshAssets>,
     textures: Res<TextureAssets>,
     settings: Res<MapSettings>,
+    spawn_points: Res<SpawnPoints>,
+    collision_map: Res<CollisionMap>,
     materials: Res<MaterialAssets>,
+    players: Query<&Transform, With<Player>>,
     mut evs: EventReader<SpawnEvent>,
+    mut round_events: EventWriter<RoundEvent>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
     audio_sinks: Res<Assets<AudioSink>>,
 ) {
-    for SpawnEvent { handle, color, spawn_type } in evs.iter() {
+    // Players spawned this frame aren't in the query yet
+    let mut player_positions = players.iter().map(|t| t.translation.xy()).collect::<Vec<_>>();
+
+    for SpawnEvent { handle, color, spawn_type, position } in evs.iter() {
         match spawn_type {
             SpawnType::Zombie => {
                 let direction_vector =
                     Vec2::new(rng.rand::<f32>() * 2.0 - 1.0, rng.rand::<f32>() * 2.0 - 1.0)
                         .normalize();
 
-                let (x, y) = random_map_point(settings.width, settings.height, &rng);
+                let Vec2 { x, y } = position.unwrap_or_else(|| {
+                    let radius = zombie_settings::DEFAULT_ZOMBIE_SIZE.1 / 2.;
+                    spawn_points.zombie_spawn(radius, &rng, &settings, &collision_map)
+                });
                 let transform =
                     Transform::default().with_translation(Vec3::new(x, y, 10.0)).with_rotation(
                         Quat::from_rotation_z(-direction_vector.x.atan2(direction_vector.y)),
@@ -134,8 +128,28 @@ pub fn spawning(
                 let size = zombie_settings::DEFAULT_ZOMBIE_SIZE.0;
                 spawn_zombie(&mut commands, transform, direction_vector, size);
             }
+            SpawnType::Boss => {
+                let direction_vector = Vec2::Y;
+                let Vec2 { x, y } = position.unwrap_or_else(|| {
+                    let radius = boss_settings::BOSS_SIZE / 2.;
+                    spawn_points.zombie_spawn(radius, &rng, &settings, &collision_map)
+                });
+                let transform = Transform::default().with_translation(Vec3::new(x, y, 10.0));
+
+                spawn_boss(&mut commands, transform, direction_vector);
+                round_events.send(RoundEvent::BossSpawned);
+            }
             SpawnType::Player => {
-                let (x, y) = random_map_point(settings.width, settings.height, &rng);
+                let Vec2 { x, y } = position.unwrap_or_else(|| {
+                    spawn_points.player_spawn(
+                        &player_positions,
+                        player_settings::DEFAULT_PLAYER_SIZE / 2.,
+                        &rng,
+                        &settings,
+                        &collision_map,
+                    )
+                });
+                player_positions.push(Vec2::new(x, y));
                 let transform = Transform::default().with_translation(Vec3::new(x, y, 10.0));
                 let handle = handle.unwrap();
                 let color = color.unwrap();
@@ -145,10 +159,9 @@ pub fn spawning(
                 } else {
                     audio_assets.tank_two.clone()
                 };
-                let music_handle = audio_sinks.get_handle(audio.play_with_settings(
-                    music,
-                    PlaybackSettings { repeat: true, volume: 0.01, speed: 1.0 },
-                ));
+                let music_handle = audio_sinks.get_handle(
+                    audio.play_with_settings(music, game_settings.music(TANK_LOOP_VOLUME)),
+                );
 
                 spawn_player(
                     &mut commands,
@@ -184,11 +197,10 @@ pub fn random_spawn_creatures(
     }
 }
 
-pub fn random_map_point(width: f32, height: f32, rng: &RandomNumbers) -> (f32, f32) {
-    let map_width = width / 2.;
-    let map_height = height / 2.;
-    let x = rng.range(-map_width, map_width);
-    let y = rng.range(-map_height, map_height);
+pub fn random_map_point(settings: &MapSettings, rng: &RandomNumbers) -> (f32, f32) {
+    let (min, max) = (settings.min(), settings.max());
+    let x = rng.range(min.x, max.x);
+    let y = rng.range(min.y, max.y);
 
     (x, y)
 }
@@ -201,13 +213,21 @@ pub fn handle_damage_events(
     mut commands: Commands,
     mut q: Query<&mut Health>,
     mut damages: EventReader<DamageEvent>,
-    mut players: Query<(&mut Player, &MusicController), Without<CreatureType>>,
-    mut zombies: Query<&mut CreatureType, Without<Player>>,
+    mut players: Query<(&mut Player, Option<&MusicController>), Without<CreatureType>>,
+    zombies: Query<(&CreatureType, &Transform, Option<&Boss>), Without<Player>>,
+    mut boss_killed: EventWriter<BossKilledEvent>,
+    mut creature_killed: EventWriter<CreatureKilledEvent>,
+    mut round_events: EventWriter<RoundEvent>,
+    textures: Res<TextureAssets>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
     audio_sinks: Res<Assets<AudioSink>>,
 ) {
     for DamageEvent { victim, attacker } in damages.iter() {
+        // Dead players keep their `Player`, so their bullets are still credited to them
+        let attacker_handle = players.get(*attacker).ok().map(|(p, _)| p.handle);
+
         if let Ok(mut health) = q.get_mut(*victim) {
             health.0 -= 1;
 
@@ -215,13 +235,29 @@ pub fn handle_damage_events(
             if let Ok((player, music_controller)) = players.get_mut(*victim) {
                 if health.0 <= 0 {
                     player.active_zombies.iter().for_each(|e| {
-                        println!("Despawning zombie: {:?}", e);
-                        commands.entity(*e).remove::<CreatureFollow>().remove::<CreatureTarget>();
+                        commands
+                            .entity(*e)
+                            .remove::<CreatureFollow>()
+                            .remove::<CreatureTarget>()
+                            .remove::<FormationSlot>();
                     });
 
-                    println!("Despawning player: {:?}", victim);
-                    audio.play(audio_assets.player_death.clone());
-                    if let Some(sink) = audio_sinks.get(&music_controller.0) {
+                    if health.0 == 0 {
+                        let cause = match (attacker_handle, zombies.get(*attacker)) {
+                            (Some(handle), _) => KillCause::Player(handle),
+                            (None, Ok((.., Some(_)))) => KillCause::Boss,
+                            (None, Ok(_)) => KillCause::Zombies,
+                            (None, Err(_)) => KillCause::Hazard,
+                        };
+                        round_events
+                            .send(RoundEvent::PlayerKilled { victim: player.handle, cause });
+                    }
+
+                    audio.play_with_settings(
+                        audio_assets.player_death.clone(),
+                        game_settings.sfx(1.0),
+                    );
+                    if let Some(sink) = music_controller.and_then(|m| audio_sinks.get(&m.0)) {
                         sink.pause();
                     }
 
@@ -240,14 +276,39 @@ pub fn handle_damage_events(
             }
 
             // Handle Zombie Cases
-            if let Ok(z_type) = zombies.get_mut(*victim) {
+            if let Ok((z_type, z_transform, boss)) = zombies.get(*victim) {
                 if health.0 <= 0 {
                     commands.entity(*victim).despawn_recursive();
-                    audio.play(audio_assets.zombie_death.clone());
+                    audio.play_with_settings(
+                        audio_assets.zombie_death.clone(),
+                        game_settings.sfx(1.0),
+                    );
 
-                    if let Some(parent) = z_type.0 {
-                        let (mut player, _) = players.get_mut(parent).unwrap();
-                        player.active_zombies.retain(|e| *e != *victim);
+                    // Health only crosses zero once, even with several hits in a frame
+                    if health.0 == 0 {
+                        creature_killed.send(CreatureKilledEvent { killer: attacker_handle });
+                    }
+                    if boss.is_some() && health.0 == 0 {
+                        boss_killed.send(BossKilledEvent {
+                            killer: *attacker,
+                            position: z_transform.translation.xy(),
+                        });
+                        round_events.send(RoundEvent::BossKilled { killer: attacker_handle });
+                    }
+
+                    // The owner may be gone already, e.g. hazards killing a dead player's horde
+                    let owner = z_type.0.and_then(|parent| players.get_mut(parent).ok());
+                    if let Some((mut player, _)) = owner {
+                        if player.active_zombies.contains(victim) {
+                            player.active_zombies.retain(|e| *e != *victim);
+
+                            // Owned zombies drop a brain for anyone to grab
+                            spawn_brain(
+                                &mut commands,
+                                textures.brain.clone(),
+                                z_transform.translation.xy(),
+                            );
+                        }
                     }
                 } else {
                 }
@@ -256,44 +317,13 @@ pub fn handle_damage_events(
     }
 }
 
-pub fn update_health(
-    players: Query<&Health, With<Player>>,
-    mut healths: Query<(&Parent, &mut Sprite), With<HealthBar>>,
-) {
-    for (parent, mut sprite) in &mut healths {
-        let parent_ent = parent.get();
-        if let Ok(health) = players.get(parent_ent) {
-            let health = health.0;
-            if health > 7 {
-                sprite.color = Color::GREEN;
-            } else if health > 3 {
-                sprite.color = Color::YELLOW;
-            } else {
-                sprite.color = Color::RED;
-            }
-        }
-    }
-}
-
-pub fn update_boost(
-    players: Query<&Boost, With<Player>>,
-    mut boosts: Query<(&Parent, &mut Sprite), With<BoostBar>>,
-) {
-    for (parent, mut sprite) in &mut boosts {
-        let parent_ent = parent.get();
-        if let Ok(boost) = players.get(parent_ent) {
-            let boost = boost.0;
-            let new_x = player_settings::BOOST_WIDTH * (boost / player_settings::BOOST_MAX);
-            sprite.custom_size = Some(Vec2::new(new_x, player_settings::BOOST_HEIGHT));
-        }
-    }
-}
-
 pub fn check_win(
     mut commands: Commands,
     player: Query<(&Player, &MusicController), Changed<Player>>,
+    stats: Res<MatchStats>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
     audio_sinks: Res<Assets<AudioSink>>,
 ) {
     let mut game_over = false;
@@ -301,6 +331,8 @@ pub fn check_win(
         if p.active_zombies.len() >= COLLECTED_ZOMBIES_TO_WIN {
             commands.insert_resource(MatchData {
                 result: format!("Player {:?} won!", get_color_name(p.color)),
+                winner: p.handle,
+                stats: stats.clone(),
             });
             game_over = true;
         }
@@ -313,7 +345,7 @@ pub fn check_win(
                 sink.stop();
             }
         }
-        audio.play(audio_assets.victory.clone());
+        audio.play_with_settings(audio_assets.victory.clone(), game_settings.sfx(1.0));
         commands.insert_resource(NextState(AppState::Win));
     }
 }
@@ -328,22 +360,31 @@ impl Plugin for RoundPlugin {
         app.add_event::<ApplyForceEvent>();
         app.add_event::<SpawnEvent>();
         app.add_event::<DamageEvent>();
+        app.add_event::<RoundEvent>();
+        app.add_event::<CreatureKilledEvent>();
 
         // Game Plugins
         app.add_plugin(PlayerPlugin)
             .add_plugin(ZombiePlugin)
             .add_plugin(BulletPlugin)
+            .add_plugin(BrainPlugin)
+            .add_plugin(BossPlugin)
+            .add_plugin(FormationPlugin)
+            .add_plugin(InteractivePlugin)
+            .add_plugin(MiniMapPlugin)
+            .add_plugin(FeedbackPlugin)
+            .add_plugin(IndicatorPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(KillFeedPlugin)
             .add_plugin(RoundUIPlugin);
 
         app.add_enter_system(AppState::InGame, setup_round);
-        app.add_system_set(
-            ConditionSet::new()
+        app.add_system(
+            handle_damage_events
                 .run_in_state(AppState::InGame)
-                .with_system(snap_to_player)
-                .with_system(update_health)
-                .with_system(update_boost)
-                .with_system(handle_damage_events.run_on_event::<DamageEvent>())
-                .into(),
+                .run_in_state(InGameState::Playing)
+                .run_on_event::<DamageEvent>()
+                .label(SystemLabels::HandleDamage),
         );
 
         ////////////////////////////////
@@ -353,6 +394,7 @@ impl Plugin for RoundPlugin {
             ConditionSet::new()
                 .label(SystemLabels::Spawning)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(spawning)
                 .with_system(random_spawn_creatures)
                 .into(),
@@ -362,7 +404,11 @@ impl Plugin for RoundPlugin {
         // Cleanup
         ////////////////////////////////
         app.add_system_set(
-            ConditionSet::new().run_in_state(AppState::InGame).with_system(check_win).into(),
+            ConditionSet::new()
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(check_win)
+                .into(),
         )
         .add_exit_system_set(
             AppState::InGame,
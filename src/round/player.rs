@@ -2,7 +2,7 @@ use bevy::math::Vec3Swizzles;
 
 use crate::round::*;
 
-use self::player_settings::{BOOST_HEIGHT, BOOST_WIDTH, SPEED_MULTIPLIER};
+use self::player_settings::SPEED_MULTIPLIER;
 
 pub mod player_settings {
     use crate::colors::*;
@@ -15,12 +15,18 @@ pub mod player_settings {
 
     pub const FOLLOW_COLLECTION_DISTANCE: f32 = 100.;
     pub const TARGET_COLLECTION_DISTANCE: f32 = 100.;
+    /// Followers further than this from their owner can be stolen
+    pub const STEAL_OWNER_DISTANCE: f32 = 250.;
+    /// Seconds a rival has to stay in range to convert a follower
+    pub const STEAL_TIME: f32 = 3.;
+    /// Scale of the progress ring once the conversion completes
+    pub const STEAL_RING_SCALE: f32 = 0.15;
 
     pub const PLAYER_COLORS: [Color; 4] = [BLUE, RED, PURPLE, GREEN];
 
+    pub const MAX_HEALTH: i32 = 10;
+
     // boost
-    pub const BOOST_WIDTH: f32 = 15.;
-    pub const BOOST_HEIGHT: f32 = 5.;
     pub const BOOST_MAX: f32 = 30.;
     pub const BOOST_PER_SECOND: f32 = 5.;
     pub const BOOST_PER_COLLECT: f32 = 5.;
@@ -42,6 +48,8 @@ pub struct Player {
     pub movement_speed: f32,
     pub attacking_zombies: u32,
     pub active_zombies: Vec<Entity>,
+    pub formation: Formation,
+    pub brains: u32,
 }
 
 impl Player {
@@ -77,7 +85,7 @@ impl PlayerBundle {
         music_controller: MusicController,
     ) -> Self {
         Self {
-            health: Health(10),
+            health: Health(player_settings::MAX_HEALTH),
             boost: Boost(player_settings::BOOST_MAX),
             sprite: SpriteBundle {
                 transform,
@@ -104,11 +112,16 @@ impl PlayerBundle {
 #[derive(Component, Default, Debug)]
 pub struct BulletReady(pub bool);
 
-#[derive(Component, Default, Debug)]
-pub struct HealthBar;
+/// A rival's follower that is being converted over to the `by` player
+#[derive(Component, Debug)]
+pub struct Converting {
+    pub by: Entity,
+    pub progress: f32,
+    pub ring: Entity,
+}
 
 #[derive(Component, Default, Debug)]
-pub struct BoostBar;
+pub struct ConvertRing;
 
 ///////////////////////////////////////////////////////////////////////////////
 
@@ -125,33 +138,10 @@ pub fn spawn_player(
     let player = commands
         .spawn_bundle(PlayerBundle::new(transform, color, texture, music_controller))
         .insert(Player::new(handle, color))
+        .insert(Tracked::Player)
         .id();
 
     commands.entity(player).add_children(|p| {
-        p.spawn_bundle(SpriteBundle {
-            transform: transform.with_translation(Vec3::new(0., -25., 10.)),
-            sprite: Sprite {
-                color: Color::GREEN,
-                custom_size: Some(Vec2::new(15., 5.)),
-                ..default()
-            },
-            ..default()
-        })
-        .insert(HealthBar)
-        .insert(RoundEntity);
-
-        p.spawn_bundle(SpriteBundle {
-            transform: transform.with_translation(Vec3::new(0., -35., 10.)),
-            sprite: Sprite {
-                color: Color::BLUE,
-                custom_size: Some(Vec2::new(BOOST_WIDTH, BOOST_HEIGHT)),
-                ..default()
-            },
-            ..default()
-        })
-        .insert(BoostBar)
-        .insert(RoundEntity);
-
         p.spawn_bundle(MaterialMesh2dBundle {
             material: color_mat,
             mesh: ring_mesh.into(),
@@ -168,19 +158,23 @@ pub struct PlayerControls {
     pub steer: f32,
     pub firing: bool,
     pub shift: bool,
+    pub formation: bool,
 }
 
 pub fn move_players(
     time: Res<Time>,
     map_settings: Res<MapSettings>,
+    collision_map: Res<CollisionMap>,
+    terrain: Res<TerrainMap>,
     mut query: Query<(&mut Transform, &PlayerControls, &Player, &mut Boost)>,
 ) {
     for (mut t, c, p, mut b) in query.iter_mut() {
+        let start = t.translation.xy();
         t.rotate_z(c.steer * p.rotation_speed * time.delta_seconds());
         apply_forward_delta(
             &time,
             &mut t,
-            p.movement_speed,
+            p.movement_speed * terrain.player_speed(start),
             if c.shift { c.accel * SPEED_MULTIPLIER } else { c.accel },
         );
         if c.shift {
@@ -188,16 +182,21 @@ pub fn move_players(
             b.0 = b.0.clamp(0.0, player_settings::BOOST_MAX);
         }
 
+        let end = collision_map.slide(start, t.translation.xy(), p.size / 2.);
+        t.translation.x = end.x;
+        t.translation.y = end.y;
+
         // constrain cube to plane
-        let (map_width, map_height) = (map_settings.width, map_settings.height);
-        t.translation.x = t.translation.x.clamp(-map_width / 2.0, map_width / 2.0);
-        t.translation.y = t.translation.y.clamp(-map_height / 2.0, map_height / 2.0);
+        let clamped = map_settings.clamp(t.translation.xy());
+        t.translation.x = clamped.x;
+        t.translation.y = clamped.y;
     }
 }
 
 pub fn kill_players(
     mut commands: Commands,
     mut damage_events: EventWriter<DamageEvent>,
+    mut hit_events: EventWriter<BulletHitEvent>,
     bullet_query: Query<(Entity, &Transform, &FiredBy), With<Bullet>>,
     player_query: Query<(Entity, &Player, &Transform), (With<Player>, Without<Bullet>)>,
 ) {
@@ -211,6 +210,7 @@ pub fn kill_players(
             if distance < (target_player.size / 2.) {
                 commands.entity(bullet_ent).despawn_recursive();
                 damage_events.send(DamageEvent::new(player_ent, fired_by.0));
+                hit_events.send(BulletHitEvent { shooter: fired_by.0 });
             }
         }
     }
@@ -219,6 +219,11 @@ pub fn kill_players(
 pub fn respawn_players(
     time: Res<Time>,
     mut commands: Commands,
+    rng: Res<RandomNumbers>,
+    settings: Res<MapSettings>,
+    spawn_points: Res<SpawnPoints>,
+    collision_map: Res<CollisionMap>,
+    alive: Query<&Transform, (With<Player>, Without<Dead>)>,
     mut respawns: Query<(Entity, &Player, &mut Clock), With<Dead>>,
     mut spawn_events: EventWriter<SpawnEvent>,
 ) {
@@ -226,11 +231,22 @@ pub fn respawn_players(
         clock.current -= time.delta_seconds();
 
         if clock.current <= 0.0 {
+            // Come back as far away from the rest of the players as the map allows
+            let others = alive.iter().map(|t| t.translation.xy()).collect::<Vec<_>>();
+            let position = spawn_points.player_spawn(
+                &others,
+                player.size / 2.,
+                &rng,
+                &settings,
+                &collision_map,
+            );
+
             commands.entity(ent).despawn_recursive();
             spawn_events.send(SpawnEvent {
                 color: Some(player.color),
                 handle: Some(player.handle),
                 spawn_type: SpawnType::Player,
+                position: Some(position),
             });
         }
     }
@@ -246,10 +262,11 @@ pub fn follow_collection(
     mut players: Query<(Entity, &mut Player, &Transform, &mut Boost)>,
     mut zombie_query: Query<
         (Entity, &mut Sprite, &Transform),
-        (With<CreatureType>, Without<CreatureFollow>, Without<CreatureTarget>),
+        (With<CreatureType>, Without<CreatureFollow>, Without<CreatureTarget>, Without<Boss>),
     >,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
 ) {
     for (player_ent, mut player, transform, mut boost) in &mut players {
         for (zombie_ent, mut sprite, _) in zombie_query.iter_mut().filter(|(_, _, t)| {
@@ -266,18 +283,140 @@ pub fn follow_collection(
             commands
                 .entity(zombie_ent)
                 .insert(CreatureType(Some(player_ent)))
-                .insert(CreatureFollow(follow_distance));
+                .insert(CreatureFollow(follow_distance))
+                .insert(FormationSlot::default());
 
-            audio.play_with_settings(
-                audio_assets.collect.clone(),
-                PlaybackSettings::ONCE.with_volume(0.5),
-            );
+            audio.play_with_settings(audio_assets.collect.clone(), game_settings.sfx(0.5));
             boost.0 += player_settings::BOOST_PER_COLLECT;
             boost.0 = boost.0.clamp(0.0, player_settings::BOOST_MAX);
         }
     }
 }
 
+pub fn steal_followers(
+    time: Res<Time>,
+    mut commands: Commands,
+    meshes: Res<MeshAssets>,
+    materials: Res<MaterialAssets>,
+    mut players: Query<(Entity, &mut Player, &Transform, &mut Boost)>,
+    mut zombie_query: Query<
+        (Entity, &mut CreatureType, &mut Sprite, &Transform, Option<&mut Converting>),
+        (With<CreatureFollow>, Without<Player>),
+    >,
+    mut rings: Query<&mut Transform, (With<ConvertRing>, Without<Player>, Without<CreatureType>)>,
+    orphans: Query<(Entity, &Converting), Without<CreatureFollow>>,
+    mut round_events: EventWriter<RoundEvent>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    game_settings: Res<GameSettings>,
+) {
+    // Followers freed by their owner's death are no longer contested
+    for (zombie_ent, c) in orphans.iter() {
+        commands.entity(c.ring).despawn_recursive();
+        commands.entity(zombie_ent).remove::<Converting>();
+    }
+
+    let positions =
+        players.iter().map(|(e, p, t, _)| (e, p.color, t.translation.xy())).collect::<Vec<_>>();
+
+    let mut converted = Vec::new();
+    for (zombie_ent, c_type, _, z_transform, converting) in zombie_query.iter_mut() {
+        let owner = match c_type.0 {
+            Some(owner) => owner,
+            None => continue,
+        };
+        let zombie_pos = z_transform.translation.xy();
+
+        // Only followers that strayed away from their owner can be stolen
+        let owner_far = positions.iter().find(|(e, _, _)| *e == owner).map_or(true, |(_, _, p)| {
+            p.distance(zombie_pos) > player_settings::STEAL_OWNER_DISTANCE
+        });
+        let stealer = positions
+            .iter()
+            .filter(|(e, _, _)| *e != owner)
+            .find(|(_, _, p)| p.distance(zombie_pos) < player_settings::FOLLOW_COLLECTION_DISTANCE)
+            .filter(|_| owner_far);
+
+        match (stealer, converting) {
+            (Some((stealer, _, _)), Some(mut c)) if c.by == *stealer => {
+                c.progress += time.delta_seconds() / player_settings::STEAL_TIME;
+                if let Ok(mut ring) = rings.get_mut(c.ring) {
+                    ring.scale =
+                        Vec3::splat(c.progress.min(1.0) * player_settings::STEAL_RING_SCALE);
+                }
+
+                if c.progress >= 1.0 {
+                    converted.push((zombie_ent, owner, *stealer));
+                    commands.entity(c.ring).despawn_recursive();
+                    commands.entity(zombie_ent).remove::<Converting>();
+                }
+            }
+            (Some((stealer, color, _)), c) => {
+                // Either a fresh contest or another player took over the contest
+                if let Some(c) = c {
+                    commands.entity(c.ring).despawn_recursive();
+                }
+
+                let ring = commands
+                    .spawn_bundle(MaterialMesh2dBundle {
+                        material: materials.get(*color),
+                        mesh: meshes.ring.clone().into(),
+                        transform: Transform::from_xyz(0., 0., -1.).with_scale(Vec3::ZERO),
+                        ..default()
+                    })
+                    .insert(ConvertRing)
+                    .insert(RoundEntity)
+                    .id();
+
+                commands.entity(zombie_ent).add_child(ring).insert(Converting {
+                    by: *stealer,
+                    progress: 0.0,
+                    ring,
+                });
+            }
+            (None, Some(c)) => {
+                commands.entity(c.ring).despawn_recursive();
+                commands.entity(zombie_ent).remove::<Converting>();
+            }
+            (None, None) => {}
+        }
+    }
+
+    // Reported once per pair of players, however many followers changed hands this frame
+    let mut stolen: Vec<(usize, usize, usize)> = Vec::new();
+    for (zombie_ent, owner, stealer) in converted {
+        if let Ok((_, mut player, _, _)) = players.get_mut(owner) {
+            player.active_zombies.retain(|e| *e != zombie_ent);
+        }
+
+        let handles = players.get(owner).ok().zip(players.get(stealer).ok());
+        if let Some(((_, from, ..), (_, by, ..))) = handles {
+            match stolen.iter_mut().find(|(f, b, _)| *f == from.handle && *b == by.handle) {
+                Some((.., count)) => *count += 1,
+                None => stolen.push((from.handle, by.handle, 1)),
+            }
+        }
+
+        if let Ok((_, mut player, _, mut boost)) = players.get_mut(stealer) {
+            player.active_zombies.push(zombie_ent);
+            boost.0 += player_settings::BOOST_PER_COLLECT;
+            boost.0 = boost.0.clamp(0.0, player_settings::BOOST_MAX);
+
+            if let Ok((_, mut c_type, mut sprite, _, _)) = zombie_query.get_mut(zombie_ent) {
+                c_type.0 = Some(stealer);
+                sprite.color = player.color;
+            }
+        }
+
+        commands.entity(zombie_ent).remove::<CreatureTarget>();
+        audio.play_with_settings(audio_assets.collect.clone(), game_settings.sfx(0.5));
+    }
+
+    for (from, by, count) in stolen {
+        round_events.send(RoundEvent::HordeStolen { from, by, count });
+    }
+}
+
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
@@ -289,6 +428,7 @@ impl Plugin for PlayerPlugin {
             ConditionSet::new()
                 .label(SystemLabels::Input)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(input.chain(apply_inputs))
                 .into(),
         )
@@ -298,6 +438,8 @@ impl Plugin for PlayerPlugin {
                 .after(SystemLabels::Input)
                 .before(SystemLabels::CameraMove)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .run_if_not(hit_stop_active)
                 .with_system(move_players)
                 .into(),
         );
@@ -309,7 +451,9 @@ impl Plugin for PlayerPlugin {
             ConditionSet::new()
                 .label(SystemLabels::Collection)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(follow_collection)
+                .with_system(steal_followers)
                 .into(),
         );
 
@@ -322,6 +466,7 @@ impl Plugin for PlayerPlugin {
                 .after(SystemLabels::PlayerMove)
                 .after(SystemLabels::BulletMove)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(kill_players)
                 .into(),
         );
@@ -330,6 +475,7 @@ impl Plugin for PlayerPlugin {
             ConditionSet::new()
                 .after(SystemLabels::PlayerDamage)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(respawn_players)
                 .into(),
         );
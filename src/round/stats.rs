@@ -0,0 +1,165 @@
+use crate::round::*;
+use bevy::utils::HashMap;
+
+///////////////////////////////////////////////////////////////////////////////
+// Stats Resources
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerStats {
+    pub color: Color,
+    pub zombies_collected: u32,
+    pub peak_horde: usize,
+    pub kills: u32,
+    pub deaths: u32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub damage_dealt: u32,
+    /// Seconds spent boosting
+    pub boost_used: f32,
+}
+
+impl PlayerStats {
+    /// Share of shots that hit something, in 0..=1
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.
+        } else {
+            (self.shots_hit as f32 / self.shots_fired as f32).min(1.)
+        }
+    }
+}
+
+/// Collected over a round, indexed by player handle. Players keep their handle across respawns
+#[derive(Debug, Default, Clone)]
+pub struct MatchStats {
+    /// Seconds of play, pauses excluded
+    pub duration: f32,
+    pub players: Vec<PlayerStats>,
+    /// Boost each player had last frame, reset with the rest of the stats
+    pub last_boost: HashMap<usize, f32>,
+}
+
+impl MatchStats {
+    pub fn player_mut(&mut self, handle: usize) -> &mut PlayerStats {
+        if self.players.len() <= handle {
+            self.players.resize_with(handle + 1, PlayerStats::default);
+        }
+        &mut self.players[handle]
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn reset_match_stats(mut commands: Commands) {
+    commands.insert_resource(MatchStats::default());
+}
+
+pub fn track_duration(time: Res<Time>, mut stats: ResMut<MatchStats>) {
+    stats.duration += time.delta_seconds();
+}
+
+/// Hordes, boost and colours are read straight off the players every frame
+pub fn track_players(mut stats: ResMut<MatchStats>, players: Query<(&Player, Option<&Boost>)>) {
+    for (player, boost) in players.iter() {
+        // Boost only goes down while boosting, refills and respawns push it back up
+        let last = boost.and_then(|boost| stats.last_boost.insert(player.handle, boost.0));
+        let spent = match (boost, last) {
+            (Some(boost), Some(last)) if boost.0 < last => last - boost.0,
+            _ => 0.,
+        };
+
+        let player_stats = stats.player_mut(player.handle);
+        player_stats.color = player.color;
+        player_stats.peak_horde = player_stats.peak_horde.max(player.active_zombies.len());
+        player_stats.boost_used += spent / player_settings::BOOST_PER_SECOND;
+    }
+}
+
+/// Collecting, stealing and boss rewards all hand the creature over to its new owner
+pub fn track_collections(
+    mut stats: ResMut<MatchStats>,
+    players: Query<&Player>,
+    creatures: Query<&CreatureType, Changed<CreatureType>>,
+) {
+    for c_type in creatures.iter() {
+        if let Some(player) = c_type.0.and_then(|owner| players.get(owner).ok()) {
+            stats.player_mut(player.handle).zombies_collected += 1;
+        }
+    }
+}
+
+pub fn track_shots(
+    mut stats: ResMut<MatchStats>,
+    mut hits: EventReader<BulletHitEvent>,
+    players: Query<&Player>,
+    bullets: Query<&FiredBy, Added<Bullet>>,
+) {
+    for fired_by in bullets.iter() {
+        if let Ok(player) = players.get(fired_by.0) {
+            stats.player_mut(player.handle).shots_fired += 1;
+        }
+    }
+
+    for BulletHitEvent { shooter } in hits.iter() {
+        if let Ok(player) = players.get(*shooter) {
+            stats.player_mut(player.handle).shots_hit += 1;
+        }
+    }
+}
+
+pub fn track_damage(
+    mut stats: ResMut<MatchStats>,
+    mut damages: EventReader<DamageEvent>,
+    players: Query<&Player>,
+) {
+    for DamageEvent { victim, attacker } in damages.iter() {
+        if let Some(attacker) = players.get(*attacker).ok().filter(|_| victim != attacker) {
+            stats.player_mut(attacker.handle).damage_dealt += 1;
+        }
+    }
+}
+
+/// Kills are credited like the kill feed does, to the hit that took the victim to zero health
+pub fn track_kills(
+    mut stats: ResMut<MatchStats>,
+    mut round_events: EventReader<RoundEvent>,
+    mut creature_kills: EventReader<CreatureKilledEvent>,
+) {
+    for event in round_events.iter() {
+        if let RoundEvent::PlayerKilled { victim, cause } = event {
+            stats.player_mut(*victim).deaths += 1;
+            if let KillCause::Player(killer) = cause {
+                if killer != victim {
+                    stats.player_mut(*killer).kills += 1;
+                }
+            }
+        }
+    }
+
+    for killer in creature_kills.iter().filter_map(|event| event.killer) {
+        stats.player_mut(killer).kills += 1;
+    }
+}
+
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BulletHitEvent>().init_resource::<MatchStats>();
+
+        app.add_enter_system(AppState::InGame, reset_match_stats);
+        app.add_system_set(
+            ConditionSet::new()
+                .after(SystemLabels::HandleDamage)
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .with_system(track_duration)
+                .with_system(track_players)
+                .with_system(track_collections)
+                .with_system(track_shots)
+                .with_system(track_damage)
+                .with_system(track_kills)
+                .into(),
+        );
+    }
+}
@@ -1,224 +1,282 @@
 use crate::round::*;
-use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    render::view::RenderLayers,
+    sprite::Anchor,
+};
 
-////////////////////////////////////////////////////////////////////////////////
-#[derive(Component)]
-pub struct P1ZombieText;
+pub mod hud_settings {
+    use bevy::prelude::Color;
 
-#[derive(Component)]
-pub struct P2ZombieText;
+    /// Just in front of the vignette, which sits right in front of the camera
+    pub const HUD_Z: f32 = -0.5;
+    /// Distance kept from the top left corner of the viewport, in screen pixels
+    pub const HUD_MARGIN: f32 = 12.;
+    pub const ROW_HEIGHT: f32 = 18.;
+    pub const FONT_SIZE: f32 = 14.;
+    pub const RESPAWN_FONT_SIZE: f32 = 28.;
 
-#[derive(Component)]
-pub struct P1RespawnText;
+    pub const BAR_WIDTH: f32 = 160.;
+    pub const BAR_HEIGHT: f32 = 10.;
+    pub const BAR_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+    pub const BOOST_COLOR: Color = Color::rgb(0.2, 0.5, 1.0);
+}
 
-#[derive(Component)]
-pub struct P2RespawnText;
+///////////////////////////////////////////////////////////////////////////////
+// HUD Components
+///////////////////////////////////////////////////////////////////////////////
 
-#[derive(Component)]
-pub struct Indicator;
+/// Root of the HUD of player `view`, a child of that player's camera
+#[derive(Component, Debug)]
+pub struct Hud {
+    pub view: usize,
+}
 
-pub fn setup_round_ui(
-    mut commands: Commands,
-    fonts: Res<FontAssets>,
-    textures: Res<TextureAssets>,
-) {
-    commands
-        .spawn_bundle(
-            TextBundle::from_sections([
-                TextSection::new(
-                    "Captured Zombies: ",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-                TextSection::new(
-                    "0",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-            ])
-            .with_style(Style {
-                align_self: AlignSelf::FlexEnd,
-                position_type: PositionType::Absolute,
-                position: UiRect { top: Val::Px(5.0), left: Val::Px(15.0), ..default() },
-                ..default()
-            }),
-        )
-        .insert(P1ZombieText)
-        .insert(RoundEntity);
+/// Where a HUD element sits, in screen pixels from the `align` point of its viewport
+#[derive(Component, Debug)]
+pub struct HudAnchor {
+    pub view: usize,
+    pub offset: Vec2,
+    pub align: HudAlign,
+}
 
-    commands
-        .spawn_bundle(
-            TextBundle::from_sections([
-                TextSection::new(
-                    "Captured Zombies: ",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-                TextSection::new(
-                    "0",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-            ])
-            .with_style(Style {
-                align_self: AlignSelf::FlexEnd,
-                position_type: PositionType::Absolute,
-                position: UiRect { top: Val::Px(5.0), left: Val::Percent(30.), ..default() },
-                ..default()
-            }),
-        )
-        .insert(P2ZombieText)
-        .insert(RoundEntity);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudAlign {
+    TopLeft,
+    TopRight,
+    Center,
+}
 
-    commands
-        .spawn_bundle(
-            TextBundle::from_sections([
-                TextSection::new(
-                    "Respawning in ",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-                TextSection::new(
-                    "0",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-                TextSection::new(
-                    " seconds",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-            ])
-            .with_style(Style {
-                align_self: AlignSelf::FlexEnd,
-                position_type: PositionType::Absolute,
-                position: UiRect { top: Val::Percent(25.0), left: Val::Percent(5.), ..default() },
-                ..default()
-            }),
-        )
-        .insert(P1RespawnText)
-        .insert(RoundEntity);
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudElement {
+    ProgressFill,
+    HealthFill,
+    BoostFill,
+    ProgressText,
+    StatusText,
+    RespawnText,
+}
 
-    commands
-        .spawn_bundle(
-            TextBundle::from_sections([
-                TextSection::new(
-                    "Respawning in ",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-                TextSection::new(
-                    "0",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-                TextSection::new(
-                    " seconds",
-                    TextStyle {
-                        font_size: 15.0,
-                        color: Color::WHITE,
-                        font: fonts.fira_sans.clone(),
-                    },
-                ),
-            ])
-            .with_style(Style {
-                align_self: AlignSelf::FlexEnd,
-                position_type: PositionType::Absolute,
-                position: UiRect { top: Val::Percent(25.0), left: Val::Percent(30.), ..default() },
-                ..default()
-            }),
-        )
-        .insert(P2RespawnText)
-        .insert(RoundEntity);
+///////////////////////////////////////////////////////////////////////////////
 
-    commands
-        .spawn_bundle(ImageBundle {
-            style: Style {
-                size: Size::new(Val::Px(30.0), Val::Auto),
-                // This takes the icons out of the flexbox flow, to be positioned exactly
-                position_type: PositionType::Absolute,
-                // The icon will be close to the left border of the button
-                position: UiRect {
-                    left: Val::Percent(35.),
-                    top: Val::Px(25.),
-                    right: Val::Auto,
-                    bottom: Val::Auto,
-                },
-                ..default()
-            },
-            image: UiImage(textures.arrow.clone()),
-            ..default()
-        })
-        .insert(Indicator)
-        .insert(SnapToPlayer(0))
-        .insert(RoundEntity);
+pub fn hud_text(text: &str, font: Handle<Font>, font_size: f32, align: HudAlign) -> Text2dBundle {
+    let horizontal = match align {
+        HudAlign::TopLeft => HorizontalAlign::Left,
+        HudAlign::TopRight => HorizontalAlign::Right,
+        HudAlign::Center => HorizontalAlign::Center,
+    };
+    Text2dBundle {
+        text: Text::from_section(text, TextStyle { font, font_size, color: Color::WHITE })
+            .with_alignment(TextAlignment { vertical: VerticalAlign::Center, horizontal }),
+        ..default()
+    }
+}
 
-    commands
-        .spawn_bundle(ImageBundle {
-            style: Style {
-                size: Size::new(Val::Px(30.0), Val::Auto),
-                // This takes the icons out of the flexbox flow, to be positioned exactly
-                position_type: PositionType::Absolute,
-                // The icon will be close to the left border of the button
-                position: UiRect {
-                    left: Val::Percent(10.),
-                    top: Val::Px(25.),
-                    right: Val::Auto,
-                    bottom: Val::Auto,
-                },
+/// A gauge is a dark background with a fill growing from its left edge
+fn spawn_hud_bar(parent: &mut ChildBuilder, view: usize, row: f32, kind: HudElement, color: Color) {
+    let layers = RenderLayers::layer(if view == 1 { RIGHT_VIEW_LAYER } else { LEFT_VIEW_LAYER });
+    let offset = Vec2::new(hud_settings::HUD_MARGIN, -hud_settings::HUD_MARGIN - row);
+
+    for (sprite_color, element, z) in
+        [(hud_settings::BAR_BACKGROUND, None, 0.), (color, Some(kind), 0.1)]
+    {
+        let mut bar = parent.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: sprite_color,
+                custom_size: Some(Vec2::new(hud_settings::BAR_WIDTH, hud_settings::BAR_HEIGHT)),
+                anchor: Anchor::CenterLeft,
                 ..default()
             },
-            image: UiImage(textures.arrow.clone()),
+            transform: Transform::from_xyz(0., 0., z),
             ..default()
-        })
-        .insert(Indicator)
-        .insert(SnapToPlayer(1))
-        .insert(RoundEntity);
+        });
+        bar.insert(HudAnchor { view, offset, align: HudAlign::TopLeft }).insert(layers);
+        if let Some(element) = element {
+            bar.insert(element);
+        }
+    }
 }
 
-fn update_round_text(
-    players: Query<&Player>,
-    mut texts: ParamSet<(
-        Query<&mut Text, With<P1ZombieText>>,
-        Query<&mut Text, With<P2ZombieText>>,
-    )>,
+/// Each game camera carries its own HUD on its view layer, so it follows the viewport layout
+pub fn setup_hud(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    cameras: Query<(Entity, Option<&RightCamera>), (With<CameraFollow>, Without<MiniMapCamera>)>,
 ) {
-    let players = players.iter().collect::<Vec<_>>();
-    if let Some(p1) = players.get(0) {
-        for mut text in &mut texts.p0() {
-            text.sections[1].value = p1.active_zombies.len().to_string();
+    for (camera, right) in cameras.iter() {
+        let view = if right.is_some() { 1 } else { 0 };
+        let layers =
+            RenderLayers::layer(if view == 1 { RIGHT_VIEW_LAYER } else { LEFT_VIEW_LAYER });
+        let font = fonts.fira_sans.clone();
+        let row = hud_settings::ROW_HEIGHT;
+        let text_offset =
+            |row: f32| Vec2::new(hud_settings::HUD_MARGIN, -hud_settings::HUD_MARGIN - row);
+
+        let hud = commands
+            .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+                0.,
+                0.,
+                hud_settings::HUD_Z,
+            )))
+            .insert(Hud { view })
+            .insert(RoundEntity)
+            .with_children(|p| {
+                spawn_hud_bar(p, view, 0., HudElement::ProgressFill, Color::WHITE);
+                p.spawn_bundle(hud_text(
+                    "",
+                    font.clone(),
+                    hud_settings::FONT_SIZE,
+                    HudAlign::TopLeft,
+                ))
+                .insert(HudElement::ProgressText)
+                .insert(HudAnchor {
+                    view,
+                    offset: text_offset(0.) + Vec2::X * (hud_settings::BAR_WIDTH + 8.),
+                    align: HudAlign::TopLeft,
+                })
+                .insert(layers);
+
+                spawn_hud_bar(p, view, row, HudElement::HealthFill, Color::GREEN);
+                spawn_hud_bar(p, view, row * 2., HudElement::BoostFill, hud_settings::BOOST_COLOR);
+
+                p.spawn_bundle(hud_text(
+                    "",
+                    font.clone(),
+                    hud_settings::FONT_SIZE,
+                    HudAlign::TopLeft,
+                ))
+                .insert(HudElement::StatusText)
+                .insert(HudAnchor { view, offset: text_offset(row * 3.), align: HudAlign::TopLeft })
+                .insert(layers);
+
+                p.spawn_bundle(hud_text(
+                    "",
+                    font.clone(),
+                    hud_settings::RESPAWN_FONT_SIZE,
+                    HudAlign::Center,
+                ))
+                .insert(HudElement::RespawnText)
+                .insert(HudAnchor { view, offset: Vec2::ZERO, align: HudAlign::Center })
+                .insert(layers);
+            })
+            .id();
+
+        commands.entity(camera).add_child(hud);
+    }
+}
+
+/// Keeps the HUD a constant size on screen and its elements pinned to the viewport corners
+pub fn layout_hud(
+    cameras: Query<
+        (&Camera, &OrthographicProjection, Option<&RightCamera>),
+        (With<CameraFollow>, Without<MiniMapCamera>),
+    >,
+    mut huds: Query<(&Hud, &mut Transform), Without<HudAnchor>>,
+    mut anchors: Query<(&HudAnchor, &mut Transform), Without<Hud>>,
+) {
+    for (camera, projection, right) in cameras.iter() {
+        let view = if right.is_some() { 1 } else { 0 };
+        let viewport = match camera.logical_viewport_size() {
+            Some(viewport) => viewport,
+            None => continue,
+        };
+
+        for (_, mut t) in huds.iter_mut().filter(|(hud, _)| hud.view == view) {
+            t.scale = Vec3::splat(projection.scale);
+        }
+
+        let half = viewport / 2.;
+        for (anchor, mut t) in anchors.iter_mut().filter(|(a, _)| a.view == view) {
+            let origin = match anchor.align {
+                HudAlign::TopLeft => Vec2::new(-half.x, half.y),
+                HudAlign::TopRight => half,
+                HudAlign::Center => Vec2::ZERO,
+            };
+            let position = origin + anchor.offset;
+            t.translation.x = position.x;
+            t.translation.y = position.y;
         }
     }
-    if let Some(p2) = players.get(1) {
-        for mut text in &mut texts.p1() {
-            text.sections[1].value = p2.active_zombies.len().to_string();
+}
+
+pub fn update_hud(
+    players: Query<(
+        &Player,
+        Option<&Health>,
+        Option<&Boost>,
+        Option<&BulletReady>,
+        Option<&PlayerControls>,
+        Option<&Clock>,
+        Option<&Dead>,
+    )>,
+    mut elements: Query<(&HudElement, &HudAnchor, &mut Sprite, &mut Visibility)>,
+    mut texts: Query<(&HudElement, &HudAnchor, &mut Text, &mut Visibility), Without<Sprite>>,
+) {
+    for (player, health, boost, ready, controls, clock, dead) in players.iter() {
+        let view = player.handle;
+        let horde = player.active_zombies.len();
+        let progress = (horde as f32 / COLLECTED_ZOMBIES_TO_WIN as f32).min(1.);
+        let health = health.map_or(0, |h| h.0);
+        let boost = boost.map_or(0., |b| b.0);
+
+        for (element, _, mut sprite, mut visibility) in
+            elements.iter_mut().filter(|(_, a, ..)| a.view == view)
+        {
+            let ratio = match element {
+                HudElement::ProgressFill => {
+                    sprite.color = player.color;
+                    progress
+                }
+                HudElement::HealthFill => {
+                    sprite.color = if health > 7 {
+                        Color::GREEN
+                    } else if health > 3 {
+                        Color::YELLOW
+                    } else {
+                        Color::RED
+                    };
+                    health as f32 / player_settings::MAX_HEALTH as f32
+                }
+                HudElement::BoostFill => boost / player_settings::BOOST_MAX,
+                _ => continue,
+            };
+            let ratio = ratio.clamp(0., 1.);
+            visibility.is_visible = ratio > 0.;
+            sprite.custom_size =
+                Some(Vec2::new(hud_settings::BAR_WIDTH * ratio, hud_settings::BAR_HEIGHT));
+        }
+
+        for (element, _, mut text, mut visibility) in
+            texts.iter_mut().filter(|(_, a, ..)| a.view == view)
+        {
+            match element {
+                HudElement::ProgressText => {
+                    text.sections[0].value = format!("{} / {}", horde, COLLECTED_ZOMBIES_TO_WIN);
+                }
+                HudElement::StatusText => {
+                    let weapon = match ready {
+                        Some(BulletReady(true)) => "Ready",
+                        Some(BulletReady(false)) => "Release to fire",
+                        None => "-",
+                    };
+                    let boosting = controls.map_or(false, |c| c.shift) && boost > 0.;
+                    text.sections[0].value = format!(
+                        "Weapon: {}  Boost: {}  Formation: {:?}  Brains: {}",
+                        weapon,
+                        if boosting { "On" } else { "Off" },
+                        player.formation,
+                        player.brains
+                    );
+                }
+                HudElement::RespawnText => {
+                    visibility.is_visible = dead.is_some();
+                    if let Some(clock) = clock.filter(|_| dead.is_some()) {
+                        text.sections[0].value =
+                            format!("Respawning in {:.1}", clock.current.max(0.));
+                    }
+                }
+                _ => {}
+            }
         }
     }
 }
@@ -229,7 +287,11 @@ fn update_round_text(
 #[derive(Component)]
 struct FPSText;
 
-fn fps_text_setup(mut commands: Commands, fonts: Res<FontAssets>) {
+fn fps_text_setup(mut commands: Commands, fonts: Res<FontAssets>, settings: Res<GameSettings>) {
+    if !settings.show_fps {
+        return;
+    }
+
     commands
         .spawn_bundle(TextBundle {
             style: Style {
@@ -288,68 +350,6 @@ fn fps_text_update_system(
     }
 }
 
-////////////////////////////////////////////////////////////////////////////////
-// Respawn
-////////////////////////////////////////////////////////////////////////////////
-
-fn update_respawn_text(
-    respawns: Query<(&Player, &Clock), (With<Player>, With<Dead>, Changed<Clock>)>,
-    mut evs: EventReader<SpawnEvent>,
-    mut texts: ParamSet<(
-        Query<&mut Text, With<P1RespawnText>>,
-        Query<&mut Text, With<P2RespawnText>>,
-    )>,
-) {
-    if respawns.is_empty() {
-        texts.p0().iter_mut().for_each(|mut text| {
-            text.sections.iter_mut().for_each(|section| {
-                section.style.color.set_a(0.0);
-            });
-        });
-        texts.p1().iter_mut().for_each(|mut text| {
-            text.sections.iter_mut().for_each(|section| {
-                section.style.color.set_a(0.0);
-            });
-        });
-    }
-
-    for ev in evs.iter() {
-        if ev.spawn_type == SpawnType::Player {
-            if ev.handle.unwrap() == 0 {
-                for mut text in texts.p0().iter_mut() {
-                    text.sections.iter_mut().for_each(|sec| {
-                        sec.style.color.set_a(0.0);
-                    });
-                }
-            } else {
-                for mut text in texts.p1().iter_mut() {
-                    text.sections.iter_mut().for_each(|sec| {
-                        sec.style.color.set_a(0.0);
-                    });
-                }
-            }
-        }
-    }
-
-    for (p, c) in respawns.iter() {
-        if p.handle == 0 {
-            for mut text in texts.p1().iter_mut() {
-                text.sections.iter_mut().for_each(|sec| {
-                    sec.style.color.set_a(1.0);
-                });
-                text.sections[1].value = format!("{:.2}", c.current);
-            }
-        } else {
-            for mut text in texts.p1().iter_mut() {
-                text.sections.iter_mut().for_each(|sec| {
-                    sec.style.color.set_a(1.0);
-                });
-                text.sections[1].value = format!("{:.2}", c.current);
-            }
-        }
-    }
-}
-
 pub struct RoundUIPlugin;
 impl Plugin for RoundUIPlugin {
     fn build(&self, app: &mut App) {
@@ -357,16 +357,17 @@ impl Plugin for RoundUIPlugin {
 
         app.add_enter_system_set(
             AppState::InGame,
-            ConditionSet::new().with_system(setup_round_ui).with_system(fps_text_setup).into(),
+            ConditionSet::new().with_system(setup_hud).with_system(fps_text_setup).into(),
         );
 
         app.add_system_set(
             ConditionSet::new()
                 .run_in_state(AppState::InGame)
-                .with_system(update_round_text)
+                .with_system(update_hud)
                 .with_system(fps_text_update_system)
-                .with_system(update_respawn_text)
                 .into(),
         );
+
+        app.add_system(layout_hud.run_in_state(AppState::InGame).after(SystemLabels::CameraMove));
     }
 }
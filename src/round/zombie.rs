@@ -1,6 +1,7 @@
 use crate::round::*;
 use bevy::{math::Vec3Swizzles, tasks::ComputeTaskPool};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 pub mod zombie_settings {
@@ -25,6 +26,75 @@ pub mod zombie_settings {
     pub const ZOMBIE_ATTACK_COOLDOWN: f32 = 1.;
 }
 
+pub const FLOCKING_PRESET_PATH: &str = "flocking.ron";
+
+/// Boid weights read by `flocking_system` and `follow_system`.
+/// Defaults to the `zombie_settings` constants unless a preset was exported to
+/// `FLOCKING_PRESET_PATH`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlockingSettings {
+    pub collision_avoidance: f32,
+    pub cohesion: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub chase: f32,
+    pub vision: f32,
+}
+
+impl Default for FlockingSettings {
+    fn default() -> Self {
+        Self {
+            collision_avoidance: zombie_settings::ZOMBIE_COLLISION_AVOIDANCE,
+            cohesion: zombie_settings::ZOMBIE_COHESION,
+            separation: zombie_settings::ZOMBIE_SEPERATION,
+            alignment: zombie_settings::ZOMBIE_ALIGNMENT,
+            chase: zombie_settings::ZOMBIE_CHASE,
+            vision: zombie_settings::ZOMBIE_VISION,
+        }
+    }
+}
+
+impl FlockingSettings {
+    pub fn presets() -> [(&'static str, FlockingSettings); 4] {
+        [
+            ("Default", FlockingSettings::default()),
+            (
+                "Tight Horde",
+                FlockingSettings {
+                    cohesion: 12.,
+                    separation: 2.,
+                    alignment: 20.,
+                    ..Default::default()
+                },
+            ),
+            (
+                "Loose Swarm",
+                FlockingSettings {
+                    cohesion: 2.,
+                    separation: 8.,
+                    alignment: 5.,
+                    vision: 80.,
+                    ..Default::default()
+                },
+            ),
+            ("Relentless", FlockingSettings { chase: 30., alignment: 8., ..Default::default() }),
+        ]
+    }
+
+    pub fn load() -> Self {
+        load_ron(FLOCKING_PRESET_PATH)
+            .unwrap_or_else(|e| {
+                warn!("Failed to load {}: {}", FLOCKING_PRESET_PATH, e);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn export(&self) -> anyhow::Result<()> {
+        save_ron(FLOCKING_PRESET_PATH, self)
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Zombie Components
 ///////////////////////////////////////////////////////////////////////////////
@@ -51,6 +121,7 @@ pub fn spawn_zombie(
         .insert(CreatureType::default())
         .insert(CreatureSize(size))
         .insert(Health(2))
+        .insert(Tracked::Follower)
         .insert(RoundEntity)
         .insert(Clock::new(1.))
         .id()
@@ -78,24 +149,48 @@ pub fn apply_force_event_system(
 pub fn creatures_follow(
     time: Res<Time>,
     map_settings: Res<MapSettings>,
+    collision_map: Res<CollisionMap>,
+    terrain: Res<TerrainMap>,
     player_q: Query<(Entity, &Transform), (With<Player>, Without<CreatureType>)>,
     mut creatures: Query<
-        (&mut Transform, &crate::components::Direction, &CreatureType, &CreatureFollow),
+        (
+            &mut Transform,
+            &crate::components::Direction,
+            &CreatureType,
+            &CreatureFollow,
+            &CreatureSize,
+            Option<&FormationSlot>,
+        ),
         Without<CreatureTarget>,
     >,
 ) {
-    for (mut transform, direction, c_type, c_follow) in &mut creatures {
+    for (mut transform, direction, c_type, c_follow, size, c_slot) in &mut creatures {
         if let Some(player_transform) =
             player_q.iter().find(|(p, _)| *p == c_type.0.unwrap()).map(|(_, t)| t)
         {
-            let player_translation = player_transform.translation.xy();
+            let (target_translation, follow_distance) = match c_slot {
+                Some(slot) => (
+                    slot_position(player_transform, slot.0),
+                    formation_settings::FORMATION_ARRIVE_DISTANCE,
+                ),
+                None => (player_transform.translation.xy(), c_follow.0),
+            };
 
-            let distance = player_translation.distance(transform.translation.xy());
+            let distance = target_translation.distance(transform.translation.xy());
             let speed =
-                if distance < c_follow.0 { continue } else { zombie_settings::ZOMBIE_SPEED };
+                if distance < follow_distance { continue } else { zombie_settings::ZOMBIE_SPEED };
 
             // Move and rotate based on direction
-            move_target(&time, &mut transform, direction, speed, &map_settings);
+            move_target(
+                &time,
+                &mut transform,
+                direction,
+                speed,
+                size.0 / 2.,
+                &map_settings,
+                &collision_map,
+                &terrain,
+            );
         }
     }
 }
@@ -104,6 +199,8 @@ pub fn creatures_target(
     time: Res<Time>,
     mut commands: Commands,
     map_settings: Res<MapSettings>,
+    collision_map: Res<CollisionMap>,
+    terrain: Res<TerrainMap>,
     mut damage_events: EventWriter<DamageEvent>,
     mut player_q: Query<(Entity, &Transform, &Player), (With<Player>, Without<CreatureType>)>,
     mut query: Query<
@@ -138,7 +235,10 @@ pub fn creatures_target(
                     &mut transform,
                     direction,
                     zombie_settings::ZOMBIE_SPEED,
+                    size.0 / 2.,
                     &map_settings,
+                    &collision_map,
+                    &terrain,
                 );
             };
         } else {
@@ -152,17 +252,24 @@ fn move_target(
     transform: &mut Transform,
     direction: &crate::components::Direction,
     speed: f32,
+    radius: f32,
     map_settings: &MapSettings,
+    collision_map: &CollisionMap,
+    terrain: &TerrainMap,
 ) {
-    // Move and rotate based on direction
-    transform.translation.x += direction.0.x * speed * time.delta_seconds();
-    transform.translation.y += direction.0.y * speed * time.delta_seconds();
+    // Move and rotate based on direction, sliding along any walls in the way
+    let start = transform.translation.xy();
+    let speed = speed * terrain.zombie_speed(start);
+    let end =
+        collision_map.slide(start, start + direction.0 * speed * time.delta_seconds(), radius);
+    transform.translation.x = end.x;
+    transform.translation.y = end.y;
     transform.rotation = Quat::from_rotation_z(-direction.0.x.atan2(direction.0.y));
 
     // Clamp to map bounds
-    let (map_width, map_height) = (map_settings.width, map_settings.height);
-    transform.translation.x = transform.translation.x.clamp(-map_width / 2.0, map_width / 2.0);
-    transform.translation.y = transform.translation.y.clamp(-map_height / 2.0, map_height / 2.0);
+    let clamped = map_settings.clamp(transform.translation.xy());
+    transform.translation.x = clamped.x;
+    transform.translation.y = clamped.y;
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -191,6 +298,7 @@ pub fn zombie_grow(time: Res<Time>, mut creatures: Query<(&mut Sprite, &Creature
 pub fn kill_creatures(
     mut commands: Commands,
     mut damage_events: EventWriter<DamageEvent>,
+    mut hit_events: EventWriter<BulletHitEvent>,
     bullet_query: Query<(Entity, &Transform, &FiredBy), With<Bullet>>,
     mut creatures: Query<
         (Entity, &CreatureType, &CreatureSize, &Transform),
@@ -206,6 +314,7 @@ pub fn kill_creatures(
                 if distance < (c_size.0 / 2.) {
                     commands.entity(bullet_ent).despawn_recursive();
                     damage_events.send(DamageEvent::new(entity, fired_by.0));
+                    hit_events.send(BulletHitEvent { shooter: fired_by.0 });
                 }
             }
         }
@@ -216,16 +325,99 @@ pub fn kill_creatures(
 // Boid Systems (Flocking & Chasing)
 ////////////////////////////////////////////////////////////////////////////////
 
+pub type FlockingCreatures<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static crate::components::Direction,
+        &'static Transform,
+        &'static CreatureType,
+        &'static CreatureSize,
+    ),
+>;
+
+/// Steering a creature gets from its neighbours, each applied with its `FlockingSettings` weight
+#[derive(Debug, Default, Clone)]
+pub struct FlockingForces {
+    /// Away from each neighbour it's about to bump into
+    pub avoidance: Vec<Vec2>,
+    pub cohesion: Option<Vec2>,
+    pub alignment: Option<Vec2>,
+    pub separation: Option<Vec2>,
+}
+
+/// The forces `flocking_system` applies to `entity_a`, also drawn by the debug overlay
+pub fn flocking_forces(
+    entity_a: Entity,
+    position_a: Vec2,
+    type_a: &CreatureType,
+    size: f32,
+    creatures: &FlockingCreatures,
+    cache_grid: &CacheGrid,
+    settings: &FlockingSettings,
+) -> FlockingForces {
+    let mut forces = FlockingForces::default();
+
+    let mut average_position = Vec2::ZERO; // Cohesion
+    let mut average_direction = Vec2::ZERO; // Alignment
+    let mut average_close_position = Vec2::ZERO; // Separation
+
+    let mut vision_count = 0;
+    let mut half_vision_count = 0;
+
+    for entity_b in cache_grid
+        .get_nearby_entities(position_a, settings.vision)
+        .iter()
+        .filter(|e| **e != entity_a)
+    {
+        let (_, direction_b, transform_b, type_b, _) = match creatures.get(*entity_b) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        // Only flock with similar creatures
+        if type_a != type_b {
+            continue;
+        }
+
+        let position_b = transform_b.translation.xy();
+        let distance = position_a.distance(position_b);
+        if distance <= settings.vision {
+            vision_count += 1;
+            average_position += position_b;
+            average_direction += direction_b.0;
+        }
+        if distance <= settings.vision / 2.0 {
+            half_vision_count += 1;
+            average_close_position += position_b;
+        }
+
+        if distance <= size * 2.0 {
+            forces.avoidance.push((position_a - position_b).normalize());
+        }
+    }
+
+    if vision_count > 0 {
+        average_position /= vision_count as f32;
+        average_direction /= vision_count as f32;
+        forces.cohesion = Some((average_position - position_a).normalize());
+        forces.alignment = Some(average_direction.normalize());
+    }
+
+    if half_vision_count > 0 {
+        average_close_position /= half_vision_count as f32;
+        forces.separation = Some((position_a - average_close_position).normalize());
+    }
+
+    forces
+}
+
 pub fn flocking_system(
     cache_grid: Res<CacheGrid>,
+    settings: Res<FlockingSettings>,
     apply_force_event_handler: EventWriter<ApplyForceEvent>,
-    creatures: Query<(
-        Entity,
-        &crate::components::Direction,
-        &Transform,
-        &CreatureType,
-        &CreatureSize,
-    )>,
+    creatures: FlockingCreatures,
 ) {
     let zombie_vec = creatures.iter().collect::<Vec<_>>();
     let compute_task_pool = ComputeTaskPool::get();
@@ -236,6 +428,7 @@ pub fn flocking_system(
 
     let creatures = &creatures;
     let cache_grid = &cache_grid;
+    let settings = &*settings;
     let apply_force_event_handler = Arc::new(Mutex::new(apply_force_event_handler));
 
     compute_task_pool.scope(|scope| {
@@ -244,88 +437,39 @@ pub fn flocking_system(
 
             scope.spawn(async move {
                 for (entity_a, _, transform_a, type_a, size) in chunk {
-                    let entity_a = *entity_a;
-                    let type_a = *type_a;
-                    let position_a = transform_a.translation.xy();
-
-                    let mut average_position = Vec2::ZERO; // Cohesion
-                    let mut average_direction = Vec2::ZERO; // Alignment
-                    let mut average_close_position = Vec2::ZERO; // Separation
-
-                    let mut vision_count = 0;
-                    let mut half_vision_count = 0;
-
-                    let size = Vec2::new(size.0, size.0);
-
-                    let (collision_avoidance, cohesion, separation, alignment) = (
-                        zombie_settings::ZOMBIE_COLLISION_AVOIDANCE,
-                        zombie_settings::ZOMBIE_COHESION,
-                        zombie_settings::ZOMBIE_SEPERATION,
-                        zombie_settings::ZOMBIE_ALIGNMENT,
+                    let forces = flocking_forces(
+                        *entity_a,
+                        transform_a.translation.xy(),
+                        type_a,
+                        size.0,
+                        creatures,
+                        cache_grid,
+                        settings,
                     );
 
-                    for entity_b in cache_grid
-                        .get_nearby_entities(position_a, zombie_settings::ZOMBIE_VISION)
-                        .iter()
-                        .filter(|e| **e != entity_a)
-                    {
-                        let (_, direction_b, transform_b, type_b, _) =
-                            match creatures.get(*entity_b) {
-                                Ok(c) => c,
-                                Err(_) => continue,
-                            };
-
-                        // Only flock with similar creatures
-                        if type_a != type_b {
-                            continue;
-                        }
-
-                        let position_b = transform_b.translation.xy();
-                        let distance = position_a.distance(position_b);
-                        if distance <= zombie_settings::ZOMBIE_VISION {
-                            vision_count += 1;
-                            average_position += position_b;
-                            average_direction += direction_b.0;
-                        }
-                        if distance <= zombie_settings::ZOMBIE_VISION / 2.0 {
-                            half_vision_count += 1;
-                            average_close_position += position_b;
-                        }
-
-                        if distance <= size.max_element() * 2.0 {
-                            let away_direction = (position_a - position_b).normalize();
-                            apply_force_event_handler.lock().send(ApplyForceEvent(
-                                entity_a,
-                                away_direction,
-                                collision_avoidance,
-                            ));
-                        }
-                    }
-
-                    if vision_count > 0 {
-                        average_position /= vision_count as f32;
-                        average_direction /= vision_count as f32;
-                        let cohesion_force =
-                            (average_position - transform_a.translation.xy()).normalize();
-                        apply_force_event_handler.lock().send(ApplyForceEvent(
-                            entity_a,
-                            cohesion_force,
-                            cohesion,
+                    let mut handler = apply_force_event_handler.lock();
+                    for away_direction in forces.avoidance {
+                        handler.send(ApplyForceEvent(
+                            *entity_a,
+                            away_direction,
+                            settings.collision_avoidance,
                         ));
-                        apply_force_event_handler.lock().send(ApplyForceEvent(
-                            entity_a,
-                            average_direction.normalize(),
-                            alignment,
+                    }
+                    if let Some(cohesion_force) = forces.cohesion {
+                        handler.send(ApplyForceEvent(*entity_a, cohesion_force, settings.cohesion));
+                    }
+                    if let Some(alignment_force) = forces.alignment {
+                        handler.send(ApplyForceEvent(
+                            *entity_a,
+                            alignment_force,
+                            settings.alignment,
                         ));
                     }
-
-                    if half_vision_count > 0 {
-                        average_close_position /= half_vision_count as f32;
-                        let separation_force = (position_a - average_close_position).normalize();
-                        apply_force_event_handler.lock().send(ApplyForceEvent(
-                            entity_a,
+                    if let Some(separation_force) = forces.separation {
+                        handler.send(ApplyForceEvent(
+                            *entity_a,
                             separation_force,
-                            separation,
+                            settings.separation,
                         ));
                     }
                 }
@@ -335,10 +479,18 @@ pub fn flocking_system(
 }
 
 pub fn follow_system(
+    settings: Res<FlockingSettings>,
     players: Query<&Transform, With<Player>>,
     apply_force_event_handler: EventWriter<ApplyForceEvent>,
     creatures: Query<
-        (Entity, &Transform, &CreatureType, Option<&CreatureFollow>, Option<&CreatureTarget>),
+        (
+            Entity,
+            &Transform,
+            &CreatureType,
+            Option<&CreatureFollow>,
+            Option<&CreatureTarget>,
+            Option<&FormationSlot>,
+        ),
         Without<Player>,
     >,
 ) {
@@ -350,25 +502,36 @@ pub fn follow_system(
     }
 
     let players = &players;
+    let chase = settings.chase;
     let apply_force_event_handler = Arc::new(Mutex::new(apply_force_event_handler));
 
     compute_task_pool.scope(|scope| {
         for chunk in zombie_vec.chunks(creatures_per_thread) {
             let apply_force_event_handler = apply_force_event_handler.clone();
             scope.spawn(async move {
-                for (entity, transform, c_type, c_follow, c_target) in chunk {
+                for (entity, transform, c_type, c_follow, c_target, c_slot) in chunk {
                     let position_a = transform.translation.xy();
 
-                    let (target, dist) = if let Some(t) = c_target {
-                        (t.0, 1.0)
+                    let (target, dist, slot) = if let Some(t) = c_target {
+                        (t.0, 1.0, None)
                     } else if let Some(f) = c_follow {
-                        (c_type.0.unwrap(), f.0)
+                        match c_slot {
+                            Some(s) => (
+                                c_type.0.unwrap(),
+                                formation_settings::FORMATION_ARRIVE_DISTANCE,
+                                Some(s.0),
+                            ),
+                            None => (c_type.0.unwrap(), f.0, None),
+                        }
                     } else {
                         continue;
                     };
 
                     let player_position = match players.get(target) {
-                        Ok(player_transform) => player_transform.translation.xy(),
+                        Ok(player_transform) => match slot {
+                            Some(slot) => slot_position(player_transform, slot),
+                            None => player_transform.translation.xy(),
+                        },
                         Err(_) => continue,
                     };
 
@@ -378,7 +541,7 @@ pub fn follow_system(
                         apply_force_event_handler.lock().send(ApplyForceEvent(
                             *entity,
                             chase_direction,
-                            zombie_settings::ZOMBIE_CHASE,
+                            chase,
                         ));
                     }
                 }
@@ -390,18 +553,25 @@ pub fn follow_system(
 pub struct ZombiePlugin;
 impl Plugin for ZombiePlugin {
     fn build(&self, app: &mut App) {
+        app.insert_resource(FlockingSettings::load());
+
         // Force
         app.add_system_set(
             ConditionSet::new()
                 .label(SystemLabels::ApplyForce)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .run_if_not(hit_stop_active)
                 .with_system(zombie_grow)
                 .with_system(follow_system)
                 .with_system(flocking_system)
                 .into(),
         )
         .add_system(
-            apply_force_event_system.run_in_state(AppState::InGame).after(SystemLabels::ApplyForce),
+            apply_force_event_system
+                .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .after(SystemLabels::ApplyForce),
         );
 
         // movement
@@ -409,6 +579,8 @@ impl Plugin for ZombiePlugin {
             ConditionSet::new()
                 .label(SystemLabels::ZombieMove)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
+                .run_if_not(hit_stop_active)
                 .with_system(creatures_follow)
                 .with_system(creatures_target)
                 .into(),
@@ -418,6 +590,7 @@ impl Plugin for ZombiePlugin {
             ConditionSet::new()
                 .after(SystemLabels::ZombieMove)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(cache_grid_update_system)
                 .into(),
         );
@@ -431,6 +604,7 @@ impl Plugin for ZombiePlugin {
                 .after(SystemLabels::PlayerMove)
                 .after(SystemLabels::BulletMove)
                 .run_in_state(AppState::InGame)
+                .run_in_state(InGameState::Playing)
                 .with_system(kill_creatures)
                 .into(),
         );
@@ -0,0 +1,152 @@
+use crate::prelude::*;
+use bevy::{
+    audio::AudioSink,
+    window::{PresentMode, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+/// Engine loops are mixed well under the sound effects
+pub const TANK_LOOP_VOLUME: f32 = 0.01;
+
+pub const RESOLUTIONS: [(f32, f32); 5] =
+    [(800., 600.), (1280., 720.), (1366., 768.), (1600., 900.), (1920., 1080.)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+/// Player options, read from `SETTINGS_PATH` at startup and saved when leaving the settings menu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub display_mode: DisplayMode,
+    pub resolution: (f32, f32),
+    pub vsync: bool,
+    pub show_fps: bool,
+    /// Scales screen shake, 0 turns it off
+    pub shake_intensity: f32,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            music_volume: 1.,
+            sfx_volume: 1.,
+            display_mode: DisplayMode::Windowed,
+            resolution: (SCREEN_WIDTH as f32 * 10.0, SCREEN_HEIGHT as f32 * 10.0),
+            vsync: true,
+            show_fps: true,
+            shake_intensity: 1.,
+        }
+    }
+}
+
+impl GameSettings {
+    /// One-shot sound effect at `volume`, before the volume settings
+    pub fn sfx(&self, volume: f32) -> PlaybackSettings {
+        PlaybackSettings::ONCE.with_volume(volume * self.master_volume * self.sfx_volume)
+    }
+
+    pub fn music_volume(&self, volume: f32) -> f32 {
+        volume * self.master_volume * self.music_volume
+    }
+
+    /// Looping music at `volume`, before the volume settings
+    pub fn music(&self, volume: f32) -> PlaybackSettings {
+        PlaybackSettings { repeat: true, volume: self.music_volume(volume), speed: 1.0 }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::Immediate
+        }
+    }
+
+    /// Defaults when nothing was saved yet
+    pub fn load() -> anyhow::Result<Self> {
+        Ok(load_ron(SETTINGS_PATH)?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        save_ron(SETTINGS_PATH, self)
+    }
+}
+
+/// Why `SETTINGS_PATH` couldn't be read. Loading happens before logging is set up, so it is
+/// reported from `report_settings_error` instead
+pub struct SettingsLoadError(pub String);
+
+pub fn report_settings_error(mut commands: Commands, error: Option<Res<SettingsLoadError>>) {
+    if let Some(error) = error {
+        warn!("Failed to load {}, using the defaults: {}", SETTINGS_PATH, error.0);
+        commands.remove_resource::<SettingsLoadError>();
+    }
+}
+
+/// Pushes changed settings to the window and the running engine loops
+pub fn apply_settings(
+    settings: Res<GameSettings>,
+    mut windows: ResMut<Windows>,
+    mut applied_resolution: Local<Option<(f32, f32)>>,
+    music: Query<&MusicController>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for music_controller in music.iter() {
+        if let Some(sink) = audio_sinks.get(&music_controller.0) {
+            sink.set_volume(settings.music_volume(TANK_LOOP_VOLUME));
+        }
+    }
+
+    // The canvas size is up to the page on the web
+    if cfg!(target_arch = "wasm32") {
+        return;
+    }
+
+    // Fullscreen windows take the monitor size, so only an actual change is pushed
+    let resized = applied_resolution.replace(settings.resolution) != Some(settings.resolution);
+
+    if let Some(window) = windows.get_primary_mut() {
+        let mode_changed = window.mode() != settings.display_mode.window_mode();
+        if mode_changed {
+            window.set_mode(settings.display_mode.window_mode());
+        }
+        if settings.display_mode == DisplayMode::Windowed && (resized || mode_changed) {
+            window.set_resolution(settings.resolution.0, settings.resolution.1);
+        }
+        if window.present_mode() != settings.present_mode() {
+            window.set_present_mode(settings.present_mode());
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(report_settings_error).add_system(apply_settings);
+    }
+}
@@ -3,6 +3,7 @@ use bevy::prelude::SystemLabel;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InGameState {
     Playing,
+    Paused,
     Controls,
 }
 
@@ -12,6 +13,8 @@ pub enum AppState {
     MenuMain,
     WorldGen,
     Controls,
+    MapSelect,
+    Settings,
     InGame,
     Win,
 }
@@ -25,6 +28,7 @@ pub enum SystemLabels {
     PlayerMove,
     Collection,
     PlayerDamage,
+    HandleDamage,
 
     BulletReload,
     BulletMove,
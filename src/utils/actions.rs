@@ -9,6 +9,7 @@ pub enum GameKey {
     LocalRight,
     LocalAttack,
     LocalShift,
+    LocalFormation,
 
     // These are remote keys for when the game is running online
     Up,
@@ -17,6 +18,7 @@ pub enum GameKey {
     Right,
     Attack,
     Shift,
+    Formation,
 }
 
 impl GameKey {
@@ -29,6 +31,7 @@ impl GameKey {
             GameKey::LocalRight => keyboard_input.just_released(KeyCode::D),
             GameKey::LocalShift => keyboard_input.just_released(KeyCode::LShift),
             GameKey::LocalAttack => keyboard_input.just_released(KeyCode::Space),
+            GameKey::LocalFormation => keyboard_input.just_released(KeyCode::Q),
             //Online
             GameKey::Up => keyboard_input.just_released(KeyCode::Up),
             GameKey::Down => keyboard_input.just_released(KeyCode::Down),
@@ -36,6 +39,7 @@ impl GameKey {
             GameKey::Right => keyboard_input.just_released(KeyCode::Right),
             GameKey::Shift => keyboard_input.just_released(KeyCode::B),
             GameKey::Attack => keyboard_input.just_released(KeyCode::M),
+            GameKey::Formation => keyboard_input.just_released(KeyCode::N),
         }
     }
 
@@ -48,6 +52,7 @@ impl GameKey {
             GameKey::LocalRight => keyboard_input.pressed(KeyCode::D),
             GameKey::LocalShift => keyboard_input.pressed(KeyCode::LShift),
             GameKey::LocalAttack => keyboard_input.pressed(KeyCode::Space),
+            GameKey::LocalFormation => keyboard_input.pressed(KeyCode::Q),
             //Online
             GameKey::Up => keyboard_input.pressed(KeyCode::Up),
             GameKey::Down => keyboard_input.pressed(KeyCode::Down),
@@ -55,6 +60,7 @@ impl GameKey {
             GameKey::Right => keyboard_input.pressed(KeyCode::Right),
             GameKey::Shift => keyboard_input.pressed(KeyCode::B),
             GameKey::Attack => keyboard_input.pressed(KeyCode::M),
+            GameKey::Formation => keyboard_input.pressed(KeyCode::N),
         }
     }
 
@@ -67,6 +73,7 @@ impl GameKey {
             GameKey::LocalRight => keyboard_input.just_pressed(KeyCode::D),
             GameKey::LocalShift => keyboard_input.just_pressed(KeyCode::LShift),
             GameKey::LocalAttack => keyboard_input.just_pressed(KeyCode::Space),
+            GameKey::LocalFormation => keyboard_input.just_pressed(KeyCode::Q),
             //Online
             GameKey::Up => keyboard_input.just_pressed(KeyCode::Up),
             GameKey::Down => keyboard_input.just_pressed(KeyCode::Down),
@@ -74,6 +81,7 @@ impl GameKey {
             GameKey::Right => keyboard_input.just_pressed(KeyCode::Right),
             GameKey::Shift => keyboard_input.just_pressed(KeyCode::B),
             GameKey::Attack => keyboard_input.just_pressed(KeyCode::M),
+            GameKey::Formation => keyboard_input.just_pressed(KeyCode::N),
         }
     }
 }
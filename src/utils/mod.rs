@@ -2,8 +2,10 @@ mod actions;
 mod bevy_utils;
 pub mod colors;
 mod macros;
+mod ron_file;
 
 pub use actions::*;
 pub use bevy_utils::*;
 pub use colors::*;
 pub use macros::*;
+pub use ron_file::*;
@@ -0,0 +1,28 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads a RON file from the working directory, `None` when there is no such file yet
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_ron<T: DeserializeOwned>(path: &str) -> anyhow::Result<Option<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(ron::from_str(&contents)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_ron<T: DeserializeOwned>(_path: &str) -> anyhow::Result<Option<T>> {
+    Ok(None)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_ron<T: Serialize>(path: &str, value: &T) -> anyhow::Result<()> {
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_ron<T: Serialize>(_path: &str, _value: &T) -> anyhow::Result<()> {
+    anyhow::bail!("Saving files is not supported on the web")
+}